@@ -0,0 +1,176 @@
+use crate::hostlist::Pattern;
+use crate::view::{SortKey, ViewOptions};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+/// turm_gpu is a rust-based TUI for inspecting GPU resources in a slurm enviroment.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Args {
+    /// Replay `scontrol show nodes --json` dumps from a file or a directory
+    /// instead of calling scontrol. Directories are replayed in file name order,
+    /// one dump per refresh.
+    #[arg(long, value_name = "PATH", global = true)]
+    pub fixture: Option<PathBuf>,
+
+    /// Read from slurmrestd at this URL (e.g. `http://slurm-head:6820`) instead of calling
+    /// scontrol. The JWT token is taken from `SLURM_JWT`.
+    #[arg(long, value_name = "URL", global = true, conflicts_with = "fixture")]
+    pub slurmrestd: Option<String>,
+
+    /// Show these clusters side by side (comma separated), asking each with `scontrol -M`.
+    /// With --fixture, each cluster is read from the file or directory of its name in the fixture directory.
+    #[arg(short = 'M', long, value_name = "NAMES", value_delimiter = ',', global = true, conflicts_with = "slurmrestd")]
+    pub clusters: Vec<String>,
+
+    /// Run scontrol and squeue on this host over ssh, e.g. `login.cluster.edu`.
+    /// The connection is shared between refreshes with ssh's ControlMaster.
+    #[arg(long, value_name = "HOST", global = true, conflicts_with_all = ["fixture", "slurmrestd"])]
+    pub ssh: Option<String>,
+
+    /// The ssh client command line for --ssh, e.g. `ssh -J bastion -p 2222`.
+    #[arg(long, value_name = "COMMAND", default_value = "ssh", global = true, requires = "ssh")]
+    pub ssh_command: String,
+
+    /// REST API version for --slurmrestd, e.g. `v0.0.40`. The newest one the server answers to by default.
+    #[arg(long, value_name = "VERSION", global = true, requires = "slurmrestd")]
+    pub api_version: Option<String>,
+
+    /// Act as this user with --slurmrestd, for tokens that are not issued to a user of their
+    /// own, such as SlurmUser's. Normal tokens already carry the user name.
+    #[arg(long, value_name = "NAME", global = true, requires = "slurmrestd")]
+    pub user: Option<String>,
+
+    /// Print the node table once as plain text and exit, instead of starting the TUI.
+    #[arg(long)]
+    pub once: bool,
+
+    /// Output format for --once. Any format implies --once.
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// Only show nodes with free resources (the 'f' key).
+    #[arg(long)]
+    pub free: bool,
+
+    /// Group nodes by partition (the 's' key).
+    #[arg(long)]
+    pub group_by_partition: bool,
+
+    /// Group nodes by cluster (the 'g' key). Combines with --group-by-partition.
+    #[arg(long)]
+    pub group_by_cluster: bool,
+
+    /// Judge GPU nodes by their CPUs as well as their GPUs (turns off the GPU-only mode of the 'c' key).
+    #[arg(long)]
+    pub all_resources: bool,
+
+    /// Only show nodes with this GPU type (the 't' key).
+    #[arg(long, value_name = "TYPE")]
+    pub gpu_type: Option<String>,
+
+    /// Count the resources of drained and down nodes as free (the 'd' key).
+    #[arg(long)]
+    pub count_unavailable: bool,
+
+    /// Only count partitions I can submit to, going by their AllowAccounts, DenyAccounts,
+    /// AllowGroups and AllowQos (the 'a' key). Also applies to fit and wait.
+    #[arg(long, global = true)]
+    pub allowed_only: bool,
+
+    /// Only show nodes whose name matches PATTERN: a substring, a glob such as `gpu*`
+    /// or a hostlist such as `gpu[01-16]` (the '/' key).
+    #[arg(long, value_name = "PATTERN")]
+    pub nodes: Option<String>,
+
+    /// Sort nodes by this column, within each group (the 'o' key).
+    #[arg(long, value_enum, value_name = "COLUMN")]
+    pub sort: Option<SortKey>,
+
+    /// Sort in descending order (the 'r' key).
+    #[arg(long, requires = "sort")]
+    pub descending: bool,
+
+    /// Leave the mouse to the terminal, so text can be selected without holding Shift.
+    /// Column headers cannot be clicked then.
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Args {
+    pub fn view_options(&self) -> ViewOptions {
+        ViewOptions {
+            hide_no_free_gpus: self.free,
+            group_by_partitions: self.group_by_partition,
+            group_by_clusters: self.group_by_cluster,
+            gpu_only_mode: !self.all_resources,
+            gpu_type: self.gpu_type.clone(),
+            cluster: None,
+            count_unavailable: self.count_unavailable,
+            allowed_only: self.allowed_only,
+            node_pattern: self.nodes.as_deref().map(Pattern::new),
+            sort: self.sort,
+            sort_descending: self.descending,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// List the nodes that could start a job right now, best fit first.
+    Fit(FitArgs),
+    /// Wait until some node could start a job, then print the nodes and optionally run a command.
+    Wait(WaitArgs),
+}
+
+/// A job request, spelled like the matching `sbatch` options.
+#[derive(clap::Args, Debug)]
+pub struct FitArgs {
+    /// Generic resources per node, e.g. `gpu:a100:2`.
+    #[arg(long)]
+    pub gres: Option<String>,
+
+    /// GPUs per node as `[type:]count`, e.g. `a100:2`.
+    #[arg(short = 'G', long)]
+    pub gpus: Option<String>,
+
+    /// GPU type, e.g. `a100`.
+    #[arg(long = "type", value_name = "TYPE")]
+    pub gpu_type: Option<String>,
+
+    /// CPUs the job needs on the node.
+    #[arg(short = 'c', long)]
+    pub cpus_per_task: Option<u32>,
+
+    /// Memory per node, e.g. `64G`. Plain numbers are megabytes.
+    #[arg(long)]
+    pub mem: Option<String>,
+
+    /// Comma separated list of partitions to consider.
+    #[arg(short = 'p', long)]
+    pub partition: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct WaitArgs {
+    #[command(flatten)]
+    pub request: FitArgs,
+
+    /// Give up after this long, e.g. `90s`, `30m` or `2h`. Plain numbers are seconds.
+    #[arg(long, value_name = "DURATION")]
+    pub timeout: Option<String>,
+
+    /// Command to run once the job fits, e.g. `-- sbatch job.sh`.
+    #[arg(last = true, value_name = "COMMAND")]
+    pub command: Vec<String>,
+}
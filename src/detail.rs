@@ -0,0 +1,144 @@
+use crate::jobs::NodeJob;
+use crate::time::{format_duration, format_timestamp, seconds_until};
+use crate::view::NodeView;
+use crate::schema::number;
+use crate::{format_memory, gres, state};
+use serde_json::Value;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+
+/// Fields that `node_details` already shows in its own lines.
+const SHOWN_FIELDS: [&str; 6] = [
+    "hostname",
+    "features",
+    "active_features",
+    "boot_time",
+    "reason_set_by_user",
+    "reason_changed_at",
+];
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values.iter().map(text).collect::<Vec<_>>().join(","),
+        Value::Null => String::new(),
+        Value::Object(_) => match number(value) {
+            Some(number) => number.to_string(),
+            None => value.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
+
+fn line<'a>(label: &str, value: String) -> Spans<'a> {
+    Spans::from(vec![
+        Span::styled(format!("{:<14}", label), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::raw(value),
+    ])
+}
+
+/// Everything known about the node in `view` and the jobs on it, one line per field.
+pub fn node_details<'a>(view: &NodeView, node_jobs: &[NodeJob]) -> Vec<Spans<'a>> {
+    let node = view.node;
+    let extra = |key: &str| node.extra.get(key).map(text).filter(|value| !value.is_empty());
+    let mut lines = Vec::new();
+
+    let name = match extra("hostname") {
+        Some(hostname) if hostname != node.name => format!("{} (hostname {})", node.name, hostname),
+        _ => node.name.clone(),
+    };
+    lines.push(line("Node", name));
+    lines.push(Spans::from(vec![
+        Span::styled(format!("{:<14}", "State"), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::styled(state::label(&view.states), Style::default().fg(state::color(&view.states))),
+    ]));
+    if let Some(reason) = node.reason.as_deref().filter(|reason| !reason.is_empty()) {
+        let mut reason = reason.to_string();
+        let set_by = extra("reason_set_by_user");
+        let changed_at = node.extra.get("reason_changed_at").and_then(number).filter(|time| *time > 0);
+        if set_by.is_some() || changed_at.is_some() {
+            let details: Vec<String> = set_by.into_iter().chain(changed_at.map(format_timestamp)).collect();
+            reason = format!("{} ({})", reason, details.join(", "));
+        }
+        lines.push(line("Reason", reason));
+    }
+    lines.push(line("Partitions", node.partitions.join(", ")));
+
+    let features = extra("features").unwrap_or_default();
+    match extra("active_features") {
+        Some(active) if active != features => lines.push(line("Features", format!("{} (active: {})", features, active))),
+        _ => lines.push(line("Features", features)),
+    }
+
+    lines.push(line("CPUs", format!("{}/{} allocated, {} free", node.alloc_cpus, node.cpus, view.free_cpus)));
+    let mut memory = format!(
+        "{}/{} allocated, {} free",
+        format_memory(node.alloc_memory),
+        format_memory(node.real_memory),
+        format_memory(view.free_memory)
+    );
+    if let Some(free_mem) = node.free_mem {
+        memory.push_str(&format!(", {} free on the OS", format_memory(free_mem)));
+    }
+    lines.push(line("Memory", memory));
+
+    let used = gres::parse(node.gres_used.as_deref().unwrap_or(""));
+    let configured = gres::parse(node.gres.as_deref().unwrap_or(""));
+    if configured.is_empty() {
+        lines.push(line("GRES", "-".to_string()));
+    }
+    for (i, entry) in configured.iter().enumerate() {
+        let in_use: Vec<&gres::Gres> = used
+            .iter()
+            .filter(|used| used.name == entry.name && used.gres_type == entry.gres_type)
+            .collect();
+        let used_count: u32 = in_use.iter().map(|used| used.count).sum();
+        let indices: Vec<String> = in_use
+            .iter()
+            .flat_map(|used| used.indices.iter().map(u32::to_string))
+            .collect();
+
+        let mut description = match &entry.gres_type {
+            Some(gres_type) => format!("{}:{}  {} total, {} used", entry.name, gres_type, entry.count, used_count),
+            None => format!("{}  {} total, {} used", entry.name, entry.count, used_count),
+        };
+        if !indices.is_empty() {
+            description.push_str(&format!(" (IDX {})", indices.join(",")));
+        }
+        if !entry.sockets.is_empty() {
+            let sockets: Vec<String> = entry.sockets.iter().map(u32::to_string).collect();
+            description.push_str(&format!(", sockets {}", sockets.join(",")));
+        }
+        lines.push(line(if i == 0 { "GRES" } else { "" }, description));
+    }
+
+    if node_jobs.is_empty() {
+        lines.push(line("Jobs", "-".to_string()));
+    }
+    for (i, node_job) in node_jobs.iter().enumerate() {
+        let job = node_job.job;
+        let time_left = job
+            .end_time()
+            .map(|end_time| format!(", {} left", format_duration(seconds_until(end_time))))
+            .unwrap_or_default();
+        lines.push(line(
+            if i == 0 { "Jobs" } else { "" },
+            format!("{} {} ({}) {} GPUs{}", job.job_id, job.user_name, job.name, node_job.gpus, time_left),
+        ));
+    }
+
+    let boot_time = node.extra.get("boot_time").and_then(number).map(format_timestamp);
+    lines.push(line("Boot time", boot_time.unwrap_or_else(|| "-".to_string())));
+
+    for (key, value) in &node.extra {
+        if SHOWN_FIELDS.contains(&key.as_str()) {
+            continue;
+        }
+        let value = text(value);
+        if !value.is_empty() {
+            lines.push(line(key, value));
+        }
+    }
+
+    lines
+}
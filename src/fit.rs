@@ -0,0 +1,216 @@
+use crate::cli::FitArgs;
+use crate::partition::{self, Partition};
+use crate::{extract_gpu_types, format_memory, gres, FreeResources, Node};
+use std::error::Error;
+
+/// What a job asks for, in the terms of `sbatch`/`srun` options.
+#[derive(Debug, Default)]
+pub struct JobRequest {
+    pub gpus: u32,
+    pub gpu_type: Option<String>,
+    pub cpus: Option<u32>,
+    /// Memory per node in megabytes.
+    pub memory: Option<u64>,
+    pub partitions: Vec<String>,
+}
+
+impl JobRequest {
+    pub fn from_args(args: &FitArgs) -> Result<Self, Box<dyn Error>> {
+        let mut request = JobRequest {
+            cpus: args.cpus_per_task,
+            partitions: args
+                .partition
+                .as_deref()
+                .map(|list| list.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+
+        if let Some(gres) = &args.gres {
+            let gpus = gres::parse(gres)
+                .into_iter()
+                .find(|gres| gres.name == "gpu")
+                .ok_or_else(|| format!("No GPU in --gres={}", gres))?;
+            request.gpus = gpus.count;
+            request.gpu_type = gpus.gres_type;
+        }
+
+        if let Some(gpus) = &args.gpus {
+            // `--gpus` takes `[type:]count` like sbatch.
+            match gpus.rsplit_once(':') {
+                Some((gpu_type, count)) => {
+                    request.gpu_type = Some(gpu_type.to_string());
+                    request.gpus = count.parse().map_err(|_| format!("Invalid --gpus={}", gpus))?;
+                }
+                None => request.gpus = gpus.parse().map_err(|_| format!("Invalid --gpus={}", gpus))?,
+            }
+        }
+
+        if let Some(gpu_type) = &args.gpu_type {
+            request.gpu_type = Some(gpu_type.clone());
+        }
+
+        if let Some(mem) = &args.mem {
+            request.memory = Some(parse_memory(mem).ok_or_else(|| format!("Invalid --mem={}", mem))?);
+        }
+
+        Ok(request)
+    }
+}
+
+/// Parses a Slurm memory size such as `64G` or `500M` into megabytes. Plain numbers are megabytes.
+pub fn parse_memory(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => (&s[..i], &s[i..]),
+        None => (s, ""),
+    };
+    let value: u64 = digits.parse().ok()?;
+    match unit.to_ascii_uppercase().trim_end_matches('B') {
+        "K" => Some(value.div_ceil(1024)),
+        "" | "M" => Some(value),
+        "G" => value.checked_mul(1024),
+        "T" => value.checked_mul(1024 * 1024),
+        _ => None,
+    }
+}
+
+/// A node that can start the job right now, and what would be left on it afterwards.
+pub struct Fit<'a> {
+    pub node: &'a Node,
+    pub partition: String,
+    pub free_gpus: u32,
+    pub free_cpus: u32,
+    /// `None` when the node reports no memory.
+    pub free_memory: Option<u64>,
+    pub gpus: u32,
+    pub cpus: u32,
+    pub memory: u64,
+}
+
+impl Fit<'_> {
+    fn leftover(&self) -> (u32, u32, u64) {
+        (
+            self.free_gpus - self.gpus,
+            self.free_cpus - self.cpus,
+            self.free_memory.map_or(0, |free_memory| free_memory - self.memory),
+        )
+    }
+}
+
+/// Nodes that can start `request` immediately, best fit first.
+///
+/// The best fit leaves the fewest GPUs, then CPUs, then memory idle, so big nodes stay free for big jobs.
+pub fn find_fits<'a>(nodes: &'a [Node], partitions: &[Partition], request: &JobRequest) -> Vec<Fit<'a>> {
+    let mut fits: Vec<Fit> = nodes
+        .iter()
+        .filter_map(|node| {
+            node.partitions
+                .iter()
+                .filter(|name| request.partitions.is_empty() || request.partitions.contains(name))
+                .filter_map(|name| fit_in_partition(node, name, partitions, request))
+                .min_by_key(|fit| fit.leftover())
+        })
+        .collect();
+    fits.sort_by(|a, b| a.leftover().cmp(&b.leftover()).then_with(|| a.node.name.cmp(&b.node.name)));
+    fits
+}
+
+fn fit_in_partition<'a>(node: &'a Node, name: &str, partitions: &[Partition], request: &JobRequest) -> Option<Fit<'a>> {
+    let partition = partition::find(partitions, node, name);
+    if !partition.is_none_or(Partition::is_up) {
+        return None;
+    }
+    let gpu_type = request.gpu_type.as_deref();
+    if let Some(gpu_type) = gpu_type {
+        if !extract_gpu_types(node).iter().any(|t| t == gpu_type) {
+            return None;
+        }
+    }
+
+    let free = FreeResources::of(node, gpu_type, false);
+    let (cpus_per_gpu, memory_per_gpu) = Partition::per_gpu_defaults(partition);
+    let cpus = request.cpus.unwrap_or((request.gpus * cpus_per_gpu).max(1));
+    let memory = request.memory.unwrap_or(u64::from(request.gpus) * memory_per_gpu);
+    if !free.fits(request.gpus, cpus, memory) {
+        return None;
+    }
+
+    Some(Fit {
+        node,
+        partition: name.to_string(),
+        free_gpus: free.gpus,
+        free_cpus: free.cpus,
+        free_memory: free.memory,
+        gpus: request.gpus,
+        cpus,
+        memory,
+    })
+}
+
+/// Prints the fits, with a cluster column when they come from `--clusters` (submit with `sbatch -M`).
+pub fn print_fits(fits: &[Fit]) {
+    let show_cluster = fits.iter().any(|fit| fit.node.cluster.is_some());
+    let cluster_column = |cluster: &str| if show_cluster { format!("{:<12} ", cluster) } else { String::new() };
+    println!(
+        "{}{:<15} {:<12} {:<12} {:>9} {:>9} {:>9} {:>12} {:>12}",
+        cluster_column("Cluster"),
+        "Node", "Partition", "GPU Type", "Free GPUs", "Free CPUs", "Free Mem", "GPUs after", "CPUs after"
+    );
+    for fit in fits {
+        println!(
+            "{}{:<15} {:<12} {:<12} {:>9} {:>9} {:>9} {:>12} {:>12}",
+            cluster_column(fit.node.cluster.as_deref().unwrap_or_default()),
+            fit.node.name,
+            fit.partition,
+            extract_gpu_types(fit.node).join(","),
+            fit.free_gpus,
+            fit.free_cpus,
+            fit.free_memory.map_or("-".to_string(), format_memory),
+            fit.leftover().0,
+            fit.leftover().1,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::partition::PartitionOutput;
+    use crate::ScontrolOutput;
+
+    const FIXTURE: &str = include_str!("../fixtures/nodes.json");
+
+    fn fitting_nodes(partitions: &[Partition], request: &JobRequest) -> Vec<(String, String)> {
+        let nodes = serde_json::from_str::<ScontrolOutput>(FIXTURE).unwrap().nodes;
+        find_fits(&nodes, partitions, request)
+            .into_iter()
+            .map(|fit| (fit.node.name.clone(), fit.partition))
+            .collect()
+    }
+
+    #[test]
+    fn skips_partitions_that_are_not_up() {
+        let mut partitions = serde_json::from_str::<PartitionOutput>(FIXTURE).unwrap().partitions;
+        let request = JobRequest { gpus: 1, partitions: vec!["gpu".to_string()], ..Default::default() };
+        let gpu_fits = fitting_nodes(&partitions, &request);
+        assert!(!gpu_fits.is_empty());
+        assert!(gpu_fits.iter().all(|(_, partition)| partition == "gpu"));
+
+        let gpu = partitions.iter_mut().find(|partition| partition.name == "gpu").unwrap();
+        gpu.state = vec!["DOWN".to_string()];
+        assert_eq!(fitting_nodes(&partitions, &request), []);
+    }
+
+    #[test]
+    fn charges_the_partition_defaults_per_gpu() {
+        let partitions = serde_json::from_str::<PartitionOutput>(FIXTURE).unwrap().partitions;
+        let nodes = serde_json::from_str::<ScontrolOutput>(FIXTURE).unwrap().nodes;
+        let request = JobRequest { gpus: 2, partitions: vec!["a100".to_string()], ..Default::default() };
+        let fits = find_fits(&nodes, &partitions, &request);
+        let summary: Vec<(&str, u32, u64)> = fits.iter().map(|fit| (fit.node.name.as_str(), fit.cpus, fit.memory)).collect();
+        // DefCpuPerGPU=8, DefMemPerGPU=65536
+        assert_eq!(summary, [("gpu02", 16, 131072)]);
+        assert_eq!(Partition::per_gpu_defaults(None), (1, 1));
+    }
+}
@@ -0,0 +1,216 @@
+/// One entry of a Slurm GRES string such as `gpu:a100:4(S:0-1)` or `gres/gpu=4`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gres {
+    pub name: String,
+    pub gres_type: Option<String>,
+    pub count: u32,
+    /// Sockets the resource is bound to, from `(S:0-1)`.
+    pub sockets: Vec<u32>,
+    /// Indices of the devices in use, from `(IDX:0,3)`.
+    pub indices: Vec<u32>,
+}
+
+/// Parses a comma separated GRES list as found in the `gres`, `gres_used` and `tres` fields.
+///
+/// Entries that do not look like GRES (e.g. `cpu=4` inside a TRES string) are skipped.
+pub fn parse(s: &str) -> Vec<Gres> {
+    split_top_level(s)
+        .into_iter()
+        .filter_map(parse_entry)
+        .collect()
+}
+
+/// Sums the count of every entry named `gpu`, optionally restricted to one GPU type.
+pub fn gpu_count(list: &[Gres], gpu_type: Option<&str>) -> u32 {
+    list.iter()
+        .filter(|gres| gres.name == "gpu")
+        .filter(|gres| gpu_type.is_none() || gres.gres_type.as_deref() == gpu_type)
+        .map(|gres| gres.count)
+        .sum()
+}
+
+/// Lists the distinct GPU types in `list`, sorted. Untyped GPUs are left out.
+pub fn gpu_types(list: &[Gres]) -> Vec<String> {
+    let mut types: Vec<String> = list
+        .iter()
+        .filter(|gres| gres.name == "gpu")
+        .filter_map(|gres| gres.gres_type.clone())
+        .collect();
+    types.sort();
+    types.dedup();
+    types
+}
+
+/// Splits on commas that are not inside parentheses, so `gpu:2(IDX:0,3),gpu:1` gives two entries.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut entries = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                entries.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    entries.push(&s[start..]);
+    entries
+        .into_iter()
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty() && *entry != "(null)")
+        .collect()
+}
+
+/// More GPUs than any node has; a lone number above it after `gpu` is a type such as `1080`.
+const MAX_GPU_COUNT: u32 = 256;
+
+fn parse_entry(entry: &str) -> Option<Gres> {
+    let (spec, extras) = match entry.split_once('(') {
+        Some((spec, rest)) => (spec, Some(rest.trim_end_matches(')'))),
+        None => (entry, None),
+    };
+
+    // TRES form: `gres/gpu=4` or `gres/gpu:a100=4`.
+    let (spec, tres_count) = if let Some(tres) = spec.strip_prefix("gres/") {
+        let (spec, count) = tres.split_once('=')?;
+        (spec, Some(parse_count(count)?))
+    } else if spec.contains('=') {
+        return None;
+    } else {
+        (spec, None)
+    };
+
+    let mut fields: Vec<&str> = spec.split(':').filter(|field| *field != "no_consume").collect();
+    let name = fields.remove(0);
+    if name.is_empty() {
+        return None;
+    }
+
+    // The last field is the count after a type (`gpu:a100:4`) or on its own (`gpu:4`), unless it
+    // is a GPU model named by a number, as in `gpu:1080`.
+    let count = match tres_count {
+        Some(count) => count,
+        None => match fields.last().and_then(|field| parse_count(field)) {
+            Some(count) if fields.len() > 1 || name != "gpu" || count <= MAX_GPU_COUNT => {
+                fields.pop();
+                count
+            }
+            _ => 1,
+        },
+    };
+    let gres_type = fields.first().map(|field| field.to_string());
+
+    let mut sockets = Vec::new();
+    let mut indices = Vec::new();
+    if let Some(extras) = extras {
+        if let Some(list) = extras.strip_prefix("S:") {
+            sockets = parse_index_list(list);
+        } else if let Some(list) = extras.strip_prefix("IDX:") {
+            indices = parse_index_list(list);
+        }
+    }
+
+    Some(Gres {
+        name: name.to_string(),
+        gres_type,
+        count,
+        sockets,
+        indices,
+    })
+}
+
+/// Parses a count with an optional `K`/`M`/`G` suffix, as Slurm prints for e.g. `shard` or `mps`.
+fn parse_count(s: &str) -> Option<u32> {
+    let (digits, multiplier) = match s.chars().last()? {
+        'K' | 'k' => (&s[..s.len() - 1], 1024),
+        'M' | 'm' => (&s[..s.len() - 1], 1024 * 1024),
+        'G' | 'g' => (&s[..s.len() - 1], 1024 * 1024 * 1024),
+        _ => (s, 1),
+    };
+    digits.parse::<u32>().ok()?.checked_mul(multiplier)
+}
+
+/// Expands `0-1,4` into `[0, 1, 4]`. `N/A` and malformed parts are ignored.
+fn parse_index_list(s: &str) -> Vec<u32> {
+    let mut indices = Vec::new();
+    for part in s.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                if let (Ok(first), Ok(last)) = (first.parse::<u32>(), last.parse::<u32>()) {
+                    indices.extend(first..=last);
+                }
+            }
+            None => {
+                if let Ok(index) = part.parse::<u32>() {
+                    indices.push(index);
+                }
+            }
+        }
+    }
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gres(name: &str, gres_type: Option<&str>, count: u32, sockets: Vec<u32>, indices: Vec<u32>) -> Gres {
+        Gres {
+            name: name.to_string(),
+            gres_type: gres_type.map(str::to_string),
+            count,
+            sockets,
+            indices,
+        }
+    }
+
+    #[test]
+    fn parses_gres_entries() {
+        let cases = [
+            ("gpu:4", vec![gres("gpu", None, 4, vec![], vec![])]),
+            ("gpu:a100:4", vec![gres("gpu", Some("a100"), 4, vec![], vec![])]),
+            ("gpu:a100:4(S:0-1)", vec![gres("gpu", Some("a100"), 4, vec![0, 1], vec![])]),
+            ("gpu:1080", vec![gres("gpu", Some("1080"), 1, vec![], vec![])]),
+            ("gpu:1080:2", vec![gres("gpu", Some("1080"), 2, vec![], vec![])]),
+            ("gpu:a100:4(IDX:0-1,3)", vec![gres("gpu", Some("a100"), 4, vec![], vec![0, 1, 3])]),
+            ("gpu:a100", vec![gres("gpu", Some("a100"), 1, vec![], vec![])]),
+            ("shard:1K", vec![gres("shard", None, 1024, vec![], vec![])]),
+            (
+                "gpu:v100:4,gpu:l40s:2(S:0)",
+                vec![gres("gpu", Some("v100"), 4, vec![], vec![]), gres("gpu", Some("l40s"), 2, vec![0], vec![])],
+            ),
+            ("(null)", vec![]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parses_tres_entries() {
+        let cases = [
+            ("gres/gpu=4", vec![gres("gpu", None, 4, vec![], vec![])]),
+            ("gres/gpu:a100=2", vec![gres("gpu", Some("a100"), 2, vec![], vec![])]),
+            (
+                "cpu=64,mem=512000M,billing=64,gres/gpu=8,gres/gpu:a100=8",
+                vec![gres("gpu", None, 8, vec![], vec![]), gres("gpu", Some("a100"), 8, vec![], vec![])],
+            ),
+            ("cpu=4,mem=1G", vec![]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn counts_gpus_by_type() {
+        let list = parse("gpu:v100:4,gpu:l40s:2,shard:8");
+        assert_eq!(gpu_count(&list, None), 6);
+        assert_eq!(gpu_count(&list, Some("l40s")), 2);
+        assert_eq!(gpu_types(&list), vec!["l40s".to_string(), "v100".to_string()]);
+    }
+}
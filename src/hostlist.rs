@@ -0,0 +1,244 @@
+/// Expands a Slurm hostlist such as `gpu[01-03,10],cpu01` into host names.
+/// Zero padding of ranges is kept, so `gpu[08-10]` gives `gpu08`, `gpu09`, `gpu10`.
+pub fn expand(hostlist: &str) -> Vec<String> {
+    split_top_level(hostlist)
+        .into_iter()
+        .flat_map(expand_host)
+        .collect()
+}
+
+/// Splits on commas outside brackets.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut hosts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                hosts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    hosts.push(&s[start..]);
+    hosts.into_iter().map(str::trim).filter(|host| !host.is_empty()).collect()
+}
+
+/// Expands the first bracket of `host` and recurses for the rest, so `a[1-2]b[1-2]` works too.
+fn expand_host(host: &str) -> Vec<String> {
+    let (Some(open), Some(close)) = (host.find('['), host.find(']')) else {
+        return vec![host.to_string()];
+    };
+    if close < open {
+        return vec![host.to_string()];
+    }
+    let prefix = &host[..open];
+    let suffixes = expand_host(&host[close + 1..]);
+
+    let mut hosts = Vec::new();
+    for part in host[open + 1..close].split(',') {
+        for number in expand_range(part) {
+            for suffix in &suffixes {
+                hosts.push(format!("{}{}{}", prefix, number, suffix));
+            }
+        }
+    }
+    hosts
+}
+
+fn expand_range(part: &str) -> Vec<String> {
+    let Some((first, last)) = part.split_once('-') else {
+        return vec![part.to_string()];
+    };
+    match (first.parse::<u64>(), last.parse::<u64>()) {
+        (Ok(start), Ok(end)) if start <= end => {
+            let width = first.len();
+            (start..=end).map(|n| format!("{:0width$}", n, width = width)).collect()
+        }
+        _ => vec![part.to_string()],
+    }
+}
+
+/// A node name pattern as typed into the search: a hostlist such as `gpu[01-16]`, a glob such
+/// as `gpu*` (globs may also appear in a hostlist, e.g. `gpu[01-04],cpu*`), or else a substring.
+///
+/// Hostlists are matched range by range rather than expanded, so `gpu[0-999999999]` costs no
+/// more than `gpu[0-9]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    text: String,
+    /// One token list per host of the hostlist, `None` for a plain substring.
+    hosts: Option<Vec<Vec<Token>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Char(char),
+    /// `?`
+    AnyChar,
+    /// `*`
+    AnyRun,
+    /// A bracket such as `[01-04,7]`: any of its parts.
+    Choice(Vec<Choice>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Choice {
+    /// `01-04`: the numbers from `start` to `end`, zero padded to `width` digits.
+    Range { start: u64, end: u64, width: usize },
+    /// Any other part, such as `7`, taken literally.
+    Text(String),
+}
+
+impl Pattern {
+    pub fn new(text: &str) -> Self {
+        let text = text.trim().to_string();
+        let hosts = text
+            .contains(['[', ',', '*', '?'])
+            .then(|| split_top_level(&text).into_iter().map(tokenize).collect());
+        Pattern { text, hosts }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match &self.hosts {
+            Some(hosts) => hosts.iter().any(|tokens| glob_match(tokens, name)),
+            None => name.contains(&self.text),
+        }
+    }
+}
+
+/// Splits one host of a hostlist into tokens. A `[` without a closing `]` is taken literally.
+fn tokenize(host: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = host;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        let token = match c {
+            '[' => match rest.split_once(']') {
+                Some((parts, after)) => {
+                    rest = after;
+                    Token::Choice(parts.split(',').map(choice).collect())
+                }
+                None => Token::Char('['),
+            },
+            // A run of stars matches what one does.
+            '*' if tokens.last() == Some(&Token::AnyRun) => continue,
+            '*' => Token::AnyRun,
+            '?' => Token::AnyChar,
+            c => Token::Char(c),
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+fn choice(part: &str) -> Choice {
+    match part.split_once('-') {
+        Some((first, last)) => match (first.parse::<u64>(), last.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => Choice::Range { start, end, width: first.len() },
+            _ => Choice::Text(part.to_string()),
+        },
+        None => Choice::Text(part.to_string()),
+    }
+}
+
+impl Choice {
+    /// Whether `digits` is one of the names `expand` would give for this range.
+    fn contains(&self, digits: &str) -> bool {
+        let Choice::Range { start, end, width } = self else {
+            return false;
+        };
+        let Ok(number) = digits.parse::<u64>() else {
+            return false;
+        };
+        let unpadded = number.checked_ilog10().map_or(1, |log| log as usize + 1);
+        (*start..=*end).contains(&number) && digits.len() == unpadded.max(*width)
+    }
+}
+
+/// Matches all of `name` against `tokens`, where `*` stands for any run of characters and
+/// `?` for one.
+fn glob_match(tokens: &[Token], name: &str) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return name.is_empty();
+    };
+    match token {
+        Token::Char(c) => name.strip_prefix(*c).is_some_and(|name| glob_match(rest, name)),
+        Token::AnyChar => {
+            let mut chars = name.chars();
+            chars.next().is_some() && glob_match(rest, chars.as_str())
+        }
+        Token::AnyRun => name
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([name.len()])
+            .any(|i| glob_match(rest, &name[i..])),
+        Token::Choice(choices) => choices.iter().any(|choice| match choice {
+            Choice::Text(text) => name.strip_prefix(text.as_str()).is_some_and(|name| glob_match(rest, name)),
+            Choice::Range { .. } => {
+                let digits = name.bytes().take_while(u8::is_ascii_digit).count();
+                (1..=digits).any(|len| choice.contains(&name[..len]) && glob_match(rest, &name[len..]))
+            }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matching(pattern: &str, names: &[&'static str]) -> Vec<&'static str> {
+        let pattern = Pattern::new(pattern);
+        names.iter().copied().filter(|name| pattern.matches(name)).collect()
+    }
+
+    const NAMES: [&str; 10] = ["gpu", "gpu1", "gpu01", "gpu04", "gpu11", "gpu16", "gpu17", "gpu001", "cpu01", "cpu99"];
+
+    #[test]
+    fn matches_substrings_globs_and_hostlists() {
+        assert_eq!(matching("pu0", &NAMES), ["gpu01", "gpu04", "gpu001", "cpu01"]);
+        assert_eq!(matching("gpu*", &NAMES), ["gpu", "gpu1", "gpu01", "gpu04", "gpu11", "gpu16", "gpu17", "gpu001"]);
+        assert_eq!(matching("gpu?1", &NAMES), ["gpu01", "gpu11"]);
+        assert_eq!(matching("gpu[01-16]", &NAMES), ["gpu01", "gpu04", "gpu11", "gpu16"]);
+        assert_eq!(matching("gpu[01-04],cpu*", &NAMES), ["gpu01", "gpu04", "cpu01", "cpu99"]);
+        assert_eq!(matching("*1", &NAMES), ["gpu1", "gpu01", "gpu11", "gpu001", "cpu01"]);
+        assert_eq!(matching("gpu[1,17]", &NAMES), ["gpu1", "gpu17"]);
+    }
+
+    #[test]
+    fn matches_what_expand_gives() {
+        for hostlist in ["gpu[8-10]", "gpu[08-10]", "rack[1-2]-gpu[9-10]", "gpu[0-3,7,010-012]"] {
+            let pattern = Pattern::new(hostlist);
+            let expanded = expand(hostlist);
+            for name in ["gpu8", "gpu08", "gpu10", "gpu010", "gpu3", "gpu7", "rack2-gpu9", "rack1-gpu10", "rack3-gpu9"] {
+                assert_eq!(pattern.matches(name), expanded.iter().any(|host| host == name), "{} {}", hostlist, name);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_huge_ranges_without_expanding_them() {
+        let pattern = Pattern::new("gpu[0-999999999]");
+        assert!(pattern.matches("gpu123456"));
+        assert!(!pattern.matches("gpu1000000000"));
+        assert!(!Pattern::new("gpu[0").matches("gpu0"));
+    }
+
+    #[test]
+    fn glob_matches_whole_names() {
+        let glob = |glob: &str, name: &str| glob_match(&tokenize(glob), name);
+        assert!(glob("gpu*", "gpu"));
+        assert!(glob("g*u*1", "gpu01"));
+        assert!(glob("**", ""));
+        assert!(!glob("gpu?", "gpu"));
+        assert!(!glob("gpu", "gpu01"));
+        assert!(glob("gpü?", "gpüx"));
+    }
+}
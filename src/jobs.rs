@@ -0,0 +1,134 @@
+use crate::schema::number;
+use crate::{gres, hostlist, state, Node};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Job states that hold resources on their nodes.
+const ACTIVE_STATES: [&str; 3] = ["RUNNING", "SUSPENDED", "COMPLETING"];
+
+#[derive(Deserialize, Debug, Default)]
+pub struct SqueueOutput {
+    #[serde(default)]
+    pub jobs: Vec<Job>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Job {
+    pub job_id: u64,
+    #[serde(default)]
+    pub user_name: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub partition: String,
+    #[serde(default, deserialize_with = "state::deserialize_state")]
+    pub job_state: Vec<String>,
+    /// Hostlist of the nodes the job runs on, e.g. `gpu[01-02]`.
+    #[serde(default)]
+    pub nodes: String,
+    #[serde(default)]
+    pub end_time: Value,
+    #[serde(default)]
+    pub tres_alloc_str: String,
+    /// One GRES string per allocated node, in hostlist order.
+    #[serde(default)]
+    pub gres_detail: Vec<String>,
+    /// The cluster the job was loaded from with `--clusters`, `None` otherwise.
+    #[serde(skip)]
+    pub cluster: Option<String>,
+}
+
+impl Job {
+    pub fn is_active(&self) -> bool {
+        self.job_state.iter().any(|state| ACTIVE_STATES.contains(&state.as_str()))
+    }
+
+    pub fn node_names(&self) -> Vec<String> {
+        hostlist::expand(&self.nodes)
+    }
+
+    /// GPUs allocated to the job across all its nodes.
+    pub fn gpus(&self) -> u32 {
+        let tres = gres::parse(&self.tres_alloc_str);
+        // TRES strings list the untyped total next to the typed counts, e.g.
+        // `gres/gpu=2,gres/gpu:a100=2`, so prefer the total when it is there.
+        match tres.iter().find(|gres| gres.name == "gpu" && gres.gres_type.is_none()) {
+            Some(total) => total.count,
+            None => gres::gpu_count(&tres, None),
+        }
+    }
+
+    /// GPUs the job holds on its `index`-th node. Without a per-node breakdown the total
+    /// is split evenly between the nodes.
+    pub fn gpus_on_node(&self, index: usize, node_count: usize) -> u32 {
+        match self.gres_detail.get(index) {
+            Some(detail) => gres::gpu_count(&gres::parse(detail), None),
+            None => self.gpus() / node_count.max(1) as u32,
+        }
+    }
+
+    /// Types of the GPUs the job holds on its `index`-th node, from the per-node breakdown or
+    /// else the job's TRES. Empty when the job does not say.
+    pub fn gpu_types_on_node(&self, index: usize) -> Vec<String> {
+        let gres = self.gres_detail.get(index).unwrap_or(&self.tres_alloc_str);
+        gres::gpu_types(&gres::parse(gres))
+    }
+
+    /// Unix time at which the job hits its time limit, if known.
+    pub fn end_time(&self) -> Option<i64> {
+        number(&self.end_time).filter(|time| *time > 0)
+    }
+}
+
+/// An active job seen from one of its nodes.
+pub struct NodeJob<'a> {
+    pub job: &'a Job,
+    pub gpus: u32,
+    pub gpu_types: Vec<String>,
+}
+
+/// Active jobs keyed by every node they run on. Look nodes up with `jobs_on`.
+pub fn jobs_by_node(jobs: &[Job]) -> HashMap<String, Vec<NodeJob<'_>>> {
+    let mut by_node: HashMap<String, Vec<NodeJob>> = HashMap::new();
+    for job in jobs.iter().filter(|job| job.is_active()) {
+        let node_names = job.node_names();
+        for (index, node_name) in node_names.iter().enumerate() {
+            by_node.entry(node_key(job.cluster.as_deref(), node_name)).or_default().push(NodeJob {
+                job,
+                gpus: job.gpus_on_node(index, node_names.len()),
+                gpu_types: job.gpu_types_on_node(index),
+            });
+        }
+    }
+    by_node
+}
+
+/// The active jobs on `node`, from `jobs_by_node`.
+pub fn jobs_on<'a, 'b>(by_node: &'b HashMap<String, Vec<NodeJob<'a>>>, node: &Node) -> &'b [NodeJob<'a>] {
+    by_node
+        .get(&node_key(node.cluster.as_deref(), &node.name))
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Node names are only unique within a cluster.
+fn node_key(cluster: Option<&str>, node: &str) -> String {
+    match cluster {
+        Some(cluster) => format!("{}/{}", cluster, node),
+        None => node.to_string(),
+    }
+}
+
+/// When the first of `node_jobs` holding GPUs, of `gpu_type` if given, reaches its time limit.
+/// Jobs that do not say which type they hold are counted for every type.
+pub fn next_gpu_release(node_jobs: &[NodeJob], gpu_type: Option<&str>) -> Option<i64> {
+    node_jobs
+        .iter()
+        .filter(|node_job| node_job.gpus > 0)
+        .filter(|node_job| {
+            gpu_type.is_none_or(|gpu_type| node_job.gpu_types.is_empty() || node_job.gpu_types.iter().any(|t| t == gpu_type))
+        })
+        .filter_map(|node_job| node_job.job.end_time())
+        .min()
+}
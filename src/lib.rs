@@ -1,3 +1,6 @@
+// pyo3 0.22's #[pyfunction] expansion trips this lint on `PyResult` returns.
+#![allow(clippy::useless_conversion)]
+
 use pyo3::prelude::*;
 
 /// Formats the sum of two numbers as string.
@@ -1,183 +1,523 @@
+mod cli;
+mod detail;
+mod fit;
+mod gres;
+mod hostlist;
+mod jobs;
+mod output;
+mod partition;
+mod plain;
+mod refresh;
+mod reservation;
+mod schema;
+mod source;
+mod state;
+mod tabs;
+mod terminal;
+mod time;
+mod transport;
+mod view;
+mod wait;
+
+use clap::Parser;
 use serde::Deserialize;
-use std::time::Instant;
 use tui::{
     backend::CrosstermBackend,
-    widgets::{Block, Borders, Row, Table, Cell},
-    layout::{Constraint, Layout, Direction},
+    widgets::{Block, Borders, Row, Table, Cell, Paragraph, Tabs, Wrap},
+    text::{Span, Spans},
+    layout::{Alignment, Constraint, Layout, Direction, Rect},
     style::{Style, Color, Modifier},
     Terminal,
 };
 use crossterm::{
-    execute,
     event,
-    terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    event::{Event, KeyCode}
+    event::{Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind}
 };
-use std::collections::HashMap;
-use std::process::Command;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use core::time::Duration;
 use std::cmp::min;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::TryRecvError;
+use std::time::Instant;
+use cli::{Args, Command, OutputFormat};
+use jobs::Job;
+use partition::Partition;
+use refresh::RefreshEvent;
+use reservation::Reservation;
+use source::{FixtureSource, MultiClusterSource, NodeSource, RestSource, ScontrolSource, Snapshot};
+use tabs::Tab;
+use transport::Transport;
+use view::{NodeView, SortKey, ViewOptions};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
 
 #[derive(Deserialize, Debug)]
 struct ScontrolOutput {
     nodes: Vec<Node>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 struct Node {
     name: String,
     gres: Option<String>,
     gres_used: Option<String>,
+    #[serde(default)]
     partitions: Vec<String>,
-    cpus: u32,           
-    alloc_cpus: u32,     
+    #[serde(default, deserialize_with = "schema::deserialize_number")]
+    cpus: u32,
+    #[serde(default, deserialize_with = "schema::deserialize_number")]
+    alloc_cpus: u32,
+    #[serde(default, deserialize_with = "state::deserialize_state")]
+    state: Vec<String>,
+    #[serde(default)]
+    state_flags: Vec<String>,
+    #[serde(default)]
+    reason: Option<String>,
+    /// Memory figures are in megabytes, as scontrol reports them.
+    #[serde(default, deserialize_with = "schema::deserialize_number")]
+    real_memory: u64,
+    #[serde(default, deserialize_with = "schema::deserialize_number")]
+    alloc_memory: u64,
+    /// Called `free_memory` up to data_parser v0.0.38.
+    #[serde(default, alias = "free_memory", deserialize_with = "schema::deserialize_optional_number")]
+    free_mem: Option<u64>,
+    /// The cluster the node was loaded from with `--clusters`, `None` otherwise.
+    #[serde(skip)]
+    cluster: Option<String>,
+    /// Reservations covering the node, attached by `reservation::mark_nodes`.
+    #[serde(skip)]
+    reservations: Vec<reservation::NodeReservation>,
+    /// Every other field scontrol reports, for the detail pane.
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_json::Value>,
 }
 
-fn extract_gpu_info(node: &Node) -> (u32, u32) {
-    let total_gpus = node
-        .gres
-        .as_deref()
-        .unwrap_or("")
-        .split(',')
-        .filter_map(|gres_entry| {
-            let parts: Vec<&str> = gres_entry.split(':').collect();
-            if parts.len() >= 3 {
-                let gpu_info = parts[2];
-                if let Some((number_str, _)) = gpu_info.split_once('(') {
-                    number_str.parse::<u32>().ok()
-                } else {
-                    gpu_info.parse::<u32>().ok()
-                }
-            } else {
-                None
-            }
-        })
-        .sum();
-
-    let allocated_gpus = node
-        .gres_used
-        .as_deref()
-        .unwrap_or("")
-        .split(',')
-        .filter_map(|gres_entry| {
-            let parts: Vec<&str> = gres_entry.split(':').collect();
-            if parts.len() >= 3 {
-                let gpu_info = parts[2];
-                if let Some((number_str, _)) = gpu_info.split_once('(') {
-                    number_str.parse::<u32>().ok()
-                } else {
-                    gpu_info.parse::<u32>().ok()
-                }
-            } else {
-                None
-            }
-        })
-        .sum();
+fn extract_gpu_info(node: &Node, gpu_type: Option<&str>) -> (u32, u32) {
+    let total_gpus = gres::gpu_count(&gres::parse(node.gres.as_deref().unwrap_or("")), gpu_type);
+    let allocated_gpus = gres::gpu_count(&gres::parse(node.gres_used.as_deref().unwrap_or("")), gpu_type);
 
     (allocated_gpus, total_gpus)
 }
 
-fn is_node_fully_allocated(node: &Node, gpu_only_mode: bool) -> bool {
-    let (allocated_gpus, total_gpus) = extract_gpu_info(node);
-    let is_gpus_fully_allocated = total_gpus == 0 || allocated_gpus == total_gpus;
+fn extract_gpu_types(node: &Node) -> Vec<String> {
+    gres::gpu_types(&gres::parse(node.gres.as_deref().unwrap_or("")))
+}
+
+/// The node state together with its flags, e.g. `["IDLE", "DRAIN"]`.
+fn node_states(node: &Node) -> Vec<String> {
+    let mut states = node.state.clone();
+    states.extend(state::normalize(node.state_flags.clone()));
+    let mut seen = HashSet::new();
+    states.retain(|state| seen.insert(state.clone()));
+    states
+}
+
+/// Free GPUs and CPUs on `node`. Drained, down and otherwise unavailable nodes have none
+/// unless `count_unavailable` is set, and nodes held by a reservation the user is not in have none.
+fn extract_free_resources(node: &Node, gpu_type: Option<&str>, count_unavailable: bool) -> (u32, u32) {
+    if !count_unavailable && state::is_unavailable(&node_states(node)) || reservation::blocking(node).is_some() {
+        return (0, 0);
+    }
+    let (allocated_gpus, total_gpus) = extract_gpu_info(node, gpu_type);
+    (total_gpus.saturating_sub(allocated_gpus), node.cpus.saturating_sub(node.alloc_cpus))
+}
+
+/// Memory on `node` that Slurm can still hand out, in megabytes.
+fn extract_free_memory(node: &Node, count_unavailable: bool) -> u64 {
+    if !count_unavailable && state::is_unavailable(&node_states(node)) || reservation::blocking(node).is_some() {
+        return 0;
+    }
+    node.real_memory.saturating_sub(node.alloc_memory)
+}
+
+/// What a new job could get on a node: GPUs, CPUs and memory in megabytes.
+struct FreeResources {
+    gpus: u32,
+    cpus: u32,
+    /// `None` on nodes without a configured RealMemory, which report 1 MB, so memory cannot be
+    /// judged there.
+    memory: Option<u64>,
+}
+
+impl FreeResources {
+    fn of(node: &Node, gpu_type: Option<&str>, count_unavailable: bool) -> Self {
+        let (gpus, cpus) = extract_free_resources(node, gpu_type, count_unavailable);
+        let memory = (node.real_memory > 1).then(|| extract_free_memory(node, count_unavailable));
+        FreeResources { gpus, cpus, memory }
+    }
+
+    /// Whether a job taking `gpus`, `cpus` and `memory` megabytes fits.
+    fn fits(&self, gpus: u32, cpus: u32, memory: u64) -> bool {
+        gpus <= self.gpus && cpus <= self.cpus && self.memory.is_none_or(|free_memory| memory <= free_memory)
+    }
+
+    /// The most GPUs a job could take in `partition`, each with the partition's default CPUs
+    /// and memory.
+    fn usable_gpus(&self, partition: Option<&Partition>) -> u32 {
+        let (cpus_per_gpu, memory_per_gpu) = Partition::per_gpu_defaults(partition);
+        (0..=self.gpus)
+            .rev()
+            .find(|&gpus| self.fits(gpus, gpus * cpus_per_gpu, u64::from(gpus) * memory_per_gpu))
+            .unwrap_or(0)
+    }
+}
+
+/// Free GPUs that a job could actually use, given the CPUs and memory left on the node.
+/// The best of the node's partitions wins.
+fn extract_usable_gpus(node: &Node, partitions: &[Partition], gpu_type: Option<&str>, count_unavailable: bool) -> u32 {
+    let free = FreeResources::of(node, gpu_type, count_unavailable);
+    node.partitions
+        .iter()
+        .map(|name| free.usable_gpus(partition::find(partitions, node, name)))
+        .max()
+        .unwrap_or_else(|| free.usable_gpus(None))
+}
+
+fn format_memory(megabytes: u64) -> String {
+    if megabytes >= 1024 {
+        format!("{}G", megabytes / 1024)
+    } else {
+        format!("{}M", megabytes)
+    }
+}
+
+/// Free and total GPUs per GPU type across `nodes`, sorted by type.
+fn summarize_gpu_types<'a>(nodes: impl IntoIterator<Item = &'a Node>, count_unavailable: bool) -> Vec<(String, u32, u32)> {
+    let mut summary: HashMap<String, (u32, u32)> = HashMap::new();
+    for node in nodes {
+        for gpu_type in extract_gpu_types(node) {
+            let (_, total_gpus) = extract_gpu_info(node, Some(&gpu_type));
+            let (free_gpus, _) = extract_free_resources(node, Some(&gpu_type), count_unavailable);
+            let entry = summary.entry(gpu_type).or_default();
+            entry.0 += free_gpus;
+            entry.1 += total_gpus;
+        }
+    }
+    let mut summary: Vec<(String, u32, u32)> = summary
+        .into_iter()
+        .map(|(gpu_type, (free_gpus, total_gpus))| (gpu_type, free_gpus, total_gpus))
+        .collect();
+    summary.sort_by(|a, b| a.0.cmp(&b.0));
+    summary
+}
+
+fn is_node_fully_allocated(
+    node: &Node,
+    partitions: &[Partition],
+    gpu_only_mode: bool,
+    gpu_type: Option<&str>,
+    count_unavailable: bool,
+) -> bool {
+    let (_, total_gpus) = extract_gpu_info(node, gpu_type);
+    let (_, free_cpus) = extract_free_resources(node, gpu_type, count_unavailable);
+    let is_gpus_fully_allocated = extract_usable_gpus(node, partitions, gpu_type, count_unavailable) == 0;
     
     if gpu_only_mode && total_gpus > 0 {
         is_gpus_fully_allocated
     } else {
-        let is_cpus_fully_allocated = node.alloc_cpus == node.cpus;
+        let is_cpus_fully_allocated = free_cpus == 0;
         is_gpus_fully_allocated && is_cpus_fully_allocated
     }
 }
 
-fn load_nodes_from_command() -> Result<Vec<Node>, Box<dyn std::error::Error>> {
-    let output = Command::new("scontrol")
-        .arg("show")
-        .arg("nodes")
-        .arg("--json")
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Command failed with error: {}", stderr);
-        return Err("Failed to execute scontrol command".into());
-    }
+fn build_node_row(view: &NodeView, show_partitions: bool, show_cluster: bool) -> Row<'static> {
+    Row::new(
+        view.columns(show_partitions, show_cluster)
+            .into_iter()
+            .map(|(text, style)| Cell::from(text).style(style))
+            .collect::<Vec<_>>(),
+    )
+}
 
-    let data = String::from_utf8_lossy(&output.stdout);
-    let scontrol_output: ScontrolOutput = serde_json::from_str(&data)?;
-    Ok(scontrol_output.nodes)
+/// The widths of the columns of `view::headers(show_cluster)` in the node table.
+fn node_table_widths(show_cluster: bool) -> Vec<Constraint> {
+    let cluster_width = show_cluster.then_some(10);
+    cluster_width
+        .into_iter()
+        .chain([20, 15, 20, 12, 10, 10, 12, 11, 10, 10, 10, 10, 11, 9, 8, 40])
+        .map(Constraint::Length)
+        .collect()
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut nodes = load_nodes_from_command()?;
-    let mut scroll = 0;
-    let refresh_interval = Duration::from_secs(5);
-    let mut last_refresh = Instant::now();
-    let mut gpu_only_mode = true;
-
-    enable_raw_mode()?;
-    let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    let mut hide_no_free_gpus = false;
-    let mut group_by_partitions = false;
+/// The column of a table under screen column `x`, for a table starting at `left` that is
+/// `width` wide. Lays out `widths` as tui 0.19 lays out `Table` columns with a spacing of one
+/// and no selection.
+fn column_at(left: u16, width: u16, widths: &[Constraint], x: u16) -> Option<usize> {
+    let mut constraints: Vec<Constraint> = widths.iter().flat_map(|width| [*width, Constraint::Length(1)]).collect();
+    constraints.pop();
+    // tui lays out `Table` columns without stretching the last one to the edge. Its switch for
+    // that is crate-private, so an empty column takes up the rest of the width instead.
+    constraints.push(Constraint::Min(0));
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(constraints)
+        .split(Rect { x: 0, y: 0, width, height: 1 });
+    let mut start = left;
+    for (index, chunk) in chunks.iter().step_by(2).enumerate() {
+        if (start..start + chunk.width).contains(&x) {
+            return Some(index);
+        }
+        start += chunk.width + 1;
+    }
+    None
+}
 
-    loop {
-        let size = terminal.size()?;
-        let rows_per_page = (size.height as usize).saturating_sub(5);
+/// The node row nearest to `row`, looking in the direction of travel first.
+/// Rows without a node are partition headers.
+fn nearest_node_row(row_nodes: &[Option<usize>], row: usize, forward: bool) -> usize {
+    let row = row.min(row_nodes.len().saturating_sub(1));
+    let after = (row..row_nodes.len()).find(|&i| row_nodes[i].is_some());
+    let before = (0..=row).rev().find(|&i| row_nodes.get(i).is_some_and(Option::is_some));
+    if forward {
+        after.or(before).unwrap_or(row)
+    } else {
+        before.or(after).unwrap_or(row)
+    }
+}
 
-        let filtered_nodes: Vec<&Node> = if hide_no_free_gpus {
-            nodes
-                .iter()
-                .filter(|node| {
-                    let (allocated_gpus, total_gpus) = extract_gpu_info(node);
-                    if gpu_only_mode {
-                        // GPU-only 모드일 때는 GPU 상태만 체크
-                        if total_gpus > 0 {
-                            (total_gpus - allocated_gpus) > 0
-                        } else {
-                            node.alloc_cpus < node.cpus
-                        }
-                    } else {
-                        // 기존 모드에서는 GPU와 CPU 모두 체크
-                        (total_gpus - allocated_gpus) > 0 || node.alloc_cpus < node.cpus
-                    }
-                })
-                .collect()
-        } else {
-            nodes.iter().collect()
-        };
+/// The rows of the node table, built once per refresh or change of `options` rather than on
+/// every pass of the event loop.
+struct NodeTable {
+    options: ViewOptions,
+    show_cluster: bool,
+    rows: Vec<Row<'static>>,
+    /// The index into `nodes` of the node on each row, `None` for group headers.
+    row_nodes: Vec<Option<usize>>,
+    /// The detail pane lines of the last row shown there.
+    details: Option<(usize, Vec<Spans<'static>>)>,
+}
 
-        let grouped_nodes = if group_by_partitions {
-            let mut partition_map: HashMap<String, Vec<&Node>> = HashMap::new();
-            for node in &filtered_nodes {
-                for partition in &node.partitions {
-                    partition_map
-                        .entry(partition.clone())
-                        .or_insert_with(Vec::new)
-                        .push(*node);
-                }
+impl NodeTable {
+    fn build(nodes: &[Node], partitions: &[Partition], jobs: &[Job], options: &ViewOptions) -> Self {
+        let show_cluster = options.show_cluster_column(nodes);
+        let jobs_by_node = jobs::jobs_by_node(jobs);
+        let views = view::filter_nodes(nodes, partitions, &jobs_by_node, options);
+        // The views borrow their nodes from `nodes`, so a node's address gives back its index.
+        let index_of: HashMap<*const Node, usize> =
+            nodes.iter().enumerate().map(|(index, node)| (node as *const Node, index)).collect();
+        let node_row = |view: &NodeView, show_partitions: bool| {
+            (build_node_row(view, show_partitions, show_cluster), index_of.get(&(view.node as *const Node)).copied())
+        };
+        let mut table_rows = Vec::new();
+        if options.is_grouped() {
+            for (group_name, views_in_group) in view::group_nodes(&views, options) {
+                let mut header_cells = vec![
+                    Cell::from(group_name).style(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
+                ];
+                header_cells.resize(view::headers(show_cluster).len(), Cell::from(""));
+                table_rows.push((Row::new(header_cells), None));
+                table_rows.extend(views_in_group.into_iter().map(|view| node_row(view, !options.group_by_partitions)));
             }
-            let mut partition_list: Vec<(String, Vec<&Node>)> = partition_map.into_iter().collect();
-            partition_list.sort_by(|a, b| a.0.cmp(&b.0));
-            Some(partition_list)
         } else {
-            None
-        };
+            table_rows.extend(views.iter().map(|view| node_row(view, true)));
+        }
+        let (rows, row_nodes) = table_rows.into_iter().unzip();
+        NodeTable { options: options.clone(), show_cluster, rows, row_nodes, details: None }
+    }
+
+    /// Fills in `details` for `row`, unless it already holds them.
+    fn load_details(&mut self, row: usize, nodes: &[Node], partitions: &[Partition], jobs: &[Job]) {
+        if self.details.as_ref().is_some_and(|(details_row, _)| *details_row == row) {
+            return;
+        }
+        self.details = self.row_nodes.get(row).copied().flatten().map(|index| {
+            let node = &nodes[index];
+            let jobs_by_node = jobs::jobs_by_node(jobs);
+            let node_jobs = jobs::jobs_on(&jobs_by_node, node);
+            (row, detail::node_details(&NodeView::new(node, partitions, node_jobs, &self.options), node_jobs))
+        });
+    }
+}
+
+/// The nodes and partitions to show: all of them, or with `allowed_only` just the partitions
+/// the user may submit to.
+fn shown_nodes_and_partitions(nodes: &[Node], partitions: &[Partition], options: &ViewOptions) -> (Vec<Node>, Vec<Partition>) {
+    if options.allowed_only {
+        partition::restrict_to_allowed(nodes, partitions)
+    } else {
+        (nodes.to_vec(), partitions.to_vec())
+    }
+}
+
+/// The source the command line asks for, for one cluster of `--clusters` or the default one.
+fn open_source(args: &Args, cluster: Option<&str>) -> Result<Box<dyn NodeSource + Send>, Box<dyn std::error::Error>> {
+    Ok(match (&args.fixture, &args.slurmrestd) {
+        (Some(path), _) => match cluster {
+            Some(cluster) => Box::new(FixtureSource::new(&path.join(cluster))?),
+            None => Box::new(FixtureSource::new(path)?),
+        },
+        (None, Some(url)) => Box::new(RestSource::new(url, args.api_version.clone(), args.user.clone())?),
+        (None, None) => {
+            let transport = match &args.ssh {
+                Some(host) => Transport::ssh(host, &args.ssh_command),
+                None => Transport::Local,
+            };
+            Box::new(ScontrolSource::new(transport, cluster.map(str::to_string)))
+        }
+    })
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let mut source = if args.clusters.is_empty() {
+        open_source(&args, None)?
+    } else {
+        let clusters = args
+            .clusters
+            .iter()
+            .map(|cluster| Ok((cluster.clone(), open_source(&args, Some(cluster))?)))
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+        Box::new(MultiClusterSource::new(clusters))
+    };
+
+    if let Some(Command::Wait(wait_args)) = &args.command {
+        let status = wait::run(source.as_mut(), wait_args, args.allowed_only)?;
+        std::process::exit(status);
+    }
+
+    let mut options = args.view_options();
+    // Parts of the data that failed to load without stopping the node table, shown under it.
+    let mut warnings = Vec::new();
+    let mut all_nodes = source.load_nodes()?;
+    let mut all_partitions = source.load_partitions()?;
+    let mut reservations = source::or_warn(source.load_reservations(), "reservations", &mut warnings);
+    reservation::mark_nodes(&mut all_nodes, &reservations);
+    let (mut nodes, mut partitions) = shown_nodes_and_partitions(&all_nodes, &all_partitions, &options);
+
+    if let Some(Command::Fit(fit_args)) = &args.command {
+        for warning in warnings.iter().chain(&source.take_warnings()) {
+            eprintln!("{}", warning);
+        }
+        let request = fit::JobRequest::from_args(fit_args)?;
+        let fits = fit::find_fits(&nodes, &partitions, &request);
+        if fits.is_empty() {
+            eprintln!("No node can start this job right now.");
+            std::process::exit(1);
+        }
+        fit::print_fits(&fits);
+        return Ok(());
+    }
+    let mut jobs = source::or_warn(source.load_jobs(), "jobs", &mut warnings);
+    warnings.extend(source.take_warnings());
+    if args.once || args.format.is_some() {
+        for warning in &warnings {
+            eprintln!("{}", warning);
+        }
+    }
+    match args.format {
+        Some(OutputFormat::Json) => return output::print_json(&nodes, &partitions, &jobs, &options),
+        Some(OutputFormat::Csv) => return output::print_csv(&nodes, &partitions, &jobs, &options),
+        None if !args.once => {}
+        Some(OutputFormat::Table) | None => {
+            output::print_table(&nodes, &partitions, &jobs, &options);
+            return Ok(());
+        }
+    }
+
+    let refresh_events = refresh::spawn(source, REFRESH_INTERVAL);
+    let mut refreshing = false;
+    // The last refresh error and when the next attempt is due.
+    let mut refresh_error: Option<(String, Instant)> = None;
+    let mut last_update = chrono::Local::now();
+
+    let mut tab = Tab::Nodes;
+    let mut scroll = 0;
+    let mut job_scroll = 0;
+    let mut partition_scroll = 0;
+    let mut reservation_scroll = 0;
+    let mut selected = 0;
+    let mut show_details = false;
+    // How far the detail pane is scrolled, and for which row; another row starts at the top.
+    let mut detail_scroll: u16 = 0;
+    let mut detail_row = 0;
+    let mut detail_lines = 0;
+    let mut cached_table: Option<NodeTable> = None;
+    // The node name search being typed after '/', if any.
+    let mut search: Option<String> = None;
+
+    let shutdown = terminal::shutdown_flag()?;
+    let _terminal_guard = terminal::TerminalGuard::enter(!args.no_mouse)?;
+    let backend = CrosstermBackend::new(std::io::stdout());
+    let mut terminal = Terminal::new(backend)?;
 
-        let total_rows = if let Some(grouped_nodes) = &grouped_nodes {
-            grouped_nodes.iter().map(|(_, nodes)| nodes.len() + 1).sum()
+    while !shutdown.load(Ordering::Relaxed) {
+        let size = terminal.size()?;
+        let (table_height, detail_height) = if show_details && tab == Tab::Nodes {
+            (Constraint::Percentage(55), Constraint::Min(0))
         } else {
-            filtered_nodes.len()
+            (Constraint::Min(0), Constraint::Length(0))
         };
+        let error_height = if refresh_error.is_some() || !warnings.is_empty() { 1 } else { 0 };
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Length(1), table_height, detail_height, Constraint::Length(1), Constraint::Length(error_height)].as_ref())
+            .split(size);
+        // Table borders and header take three lines.
+        let rows_per_page = (layout[1].height as usize).saturating_sub(3).max(1);
+        let cluster_jobs: Vec<&Job> = jobs.iter().filter(|job| options.includes_cluster(&job.cluster)).collect();
+        let active_jobs = cluster_jobs.iter().filter(|job| job.is_active()).count();
+        job_scroll = job_scroll.min(active_jobs.saturating_sub(rows_per_page));
+        let cluster_partitions: Vec<&Partition> = partitions
+            .iter()
+            .filter(|partition| options.includes_cluster(&partition.cluster))
+            .collect();
+        partition_scroll = partition_scroll.min(cluster_partitions.len().saturating_sub(rows_per_page));
+        let current_reservations: Vec<&Reservation> = reservations
+            .iter()
+            .filter(|reservation| options.includes_cluster(&reservation.cluster) && !reservation.has_ended())
+            .collect();
+        reservation_scroll = reservation_scroll.min(current_reservations.len().saturating_sub(rows_per_page));
+
+        let clusters = view::clusters(&nodes);
+        if cached_table.as_ref().is_some_and(|table| table.options != options) {
+            cached_table = None;
+        }
+        let node_table = cached_table.get_or_insert_with(|| NodeTable::build(&nodes, &partitions, &jobs, &options));
+        let show_cluster = node_table.show_cluster;
+        let total_rows = node_table.row_nodes.len();
 
+        selected = nearest_node_row(&node_table.row_nodes, selected, true);
         let max_scroll = total_rows.saturating_sub(rows_per_page);
         scroll = scroll.min(max_scroll);
+        if selected < scroll {
+            scroll = selected;
+        } else if selected >= scroll + rows_per_page {
+            scroll = selected + 1 - rows_per_page;
+        }
+        if detail_row != selected {
+            detail_row = selected;
+            detail_scroll = 0;
+        }
+
+        let widths = node_table_widths(show_cluster);
+        if show_details && tab == Tab::Nodes {
+            node_table.load_details(selected, &nodes, &partitions, &jobs);
+        }
+        let node_table = &*node_table;
+        let row_nodes = &node_table.row_nodes;
 
         terminal.draw(|f| {
+            let cluster_keys = if clusters.len() > 1 {
+                format!(
+                    "'g' to toggle grouping by clusters, 'm' to cycle cluster [{}], ",
+                    options.cluster.as_deref().unwrap_or("all")
+                )
+            } else {
+                String::new()
+            };
             let title = format!(
-                "Resource Allocation (Up/Down or k/j to scroll, 'f' to toggle free node filtering, 's' to toggle grouping by partitions, 'c' to toggle GPU-only mode [{}], 'q' to quit)",
-                if gpu_only_mode { "ON" } else { "OFF" }
+                "Resource Allocation (Tab to switch tabs, Up/Down or k/j to move, Enter for details, 'f' to toggle free node filtering, 's' to toggle grouping by partitions, {}'c' to toggle GPU-only mode [{}], 't' to cycle GPU type [{}], 'd' to count drained/down nodes as free [{}], 'a' to only count partitions I can submit to [{}], 'o'/'r'{} to sort [{}], '/' to search nodes, 'q' to quit)",
+                cluster_keys,
+                if options.gpu_only_mode { "ON" } else { "OFF" },
+                options.gpu_type.as_deref().unwrap_or("all"),
+                if options.count_unavailable { "ON" } else { "OFF" },
+                if options.allowed_only { "ON" } else { "OFF" },
+                if args.no_mouse { "" } else { " or a header click" },
+                match options.sort {
+                    Some(key) => format!("{} {}", key.header(), if options.sort_descending { "▼" } else { "▲" }),
+                    None => "none".to_string(),
+                }
             );
             
             let block = Block::default()
@@ -185,110 +525,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .borders(Borders::ALL);
             f.render_widget(block, size);
 
-            let layout = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(1)
-                .constraints([Constraint::Percentage(100)].as_ref())
-                .split(size);
-
-            let mut table_rows: Vec<Row> = Vec::new();
-
-            if let Some(grouped_nodes) = &grouped_nodes {
-                for (partition_name, nodes_in_partition) in grouped_nodes {
-                    let header_cells = vec![
-                        Cell::from(partition_name.clone())
-                            .style(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
-                        Cell::from(""), Cell::from(""), Cell::from(""),
-                        Cell::from(""), Cell::from(""), Cell::from(""),
-                    ];
-                    table_rows.push(Row::new(header_cells));
-
-                    for node in nodes_in_partition {
-                        let (allocated_gpus, total_gpus) = extract_gpu_info(node);
-                        let free_gpus = total_gpus - allocated_gpus;
-                        let free_cpus = node.cpus - node.alloc_cpus;
-                        let is_fully_allocated = is_node_fully_allocated(node, gpu_only_mode);
-
-                        let partition_cell = Cell::from("");
-                        let mut name_cell = Cell::from(node.name.clone()).style(Style::default().fg(Color::Green));
-                        if is_fully_allocated {
-                            name_cell = name_cell.style(Style::default().fg(Color::Red));
-                        }
-
-                        let free_gpu_cell = Cell::from(free_gpus.to_string());
-                        let alloc_gpu_cell = Cell::from(allocated_gpus.to_string());
-                        let total_gpu_cell = Cell::from(total_gpus.to_string());
-                        let cpu_usage_cell = Cell::from(format!("{}/{}", node.alloc_cpus, node.cpus));
-                        let free_cpu_cell = Cell::from(free_cpus.to_string());
+            let tab_titles = Tab::ALL.iter().map(|tab| Spans::from(tab.title())).collect();
+            let tab_index = Tab::ALL.iter().position(|t| *t == tab).unwrap_or(0);
+            let tab_bar = Tabs::new(tab_titles)
+                .select(tab_index)
+                .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+            f.render_widget(tab_bar, layout[0]);
 
-                        let styled_free_gpu_cell = if free_gpus > 0 {
-                            free_gpu_cell.style(Style::default().fg(Color::Green))
-                        } else {
-                            free_gpu_cell
-                        };
-
-                        let styled_free_cpu_cell = if free_cpus > 0 {
-                            free_cpu_cell.style(Style::default().fg(Color::Green))
-                        } else {
-                            free_cpu_cell
-                        };
-
-                        table_rows.push(Row::new(vec![
-                            partition_cell,
-                            name_cell,
-                            styled_free_gpu_cell,
-                            alloc_gpu_cell,
-                            total_gpu_cell,
-                            cpu_usage_cell,
-                            styled_free_cpu_cell,
-                        ]));
-                    }
-                }
-            } else {
-                for node in &filtered_nodes {
-                    let (allocated_gpus, total_gpus) = extract_gpu_info(node);
-                    let free_gpus = total_gpus - allocated_gpus;
-                    let free_cpus = node.cpus - node.alloc_cpus;
-                    let is_fully_allocated = is_node_fully_allocated(node, gpu_only_mode);
-
-                    let partition_cell = Cell::from(node.partitions.join(", ")).style(Style::default().fg(Color::Blue));
-                    let mut name_cell = Cell::from(node.name.clone()).style(Style::default().fg(Color::Green));
-                    if is_fully_allocated {
-                        name_cell = name_cell.style(Style::default().fg(Color::Red));
-                    }
-
-                    let free_gpu_cell = Cell::from(free_gpus.to_string());
-                    let alloc_gpu_cell = Cell::from(allocated_gpus.to_string());
-                    let total_gpu_cell = Cell::from(total_gpus.to_string());
-                    let cpu_usage_cell = Cell::from(format!("{}/{}", node.alloc_cpus, node.cpus));
-                    let free_cpu_cell = Cell::from(free_cpus.to_string());
-
-                    let styled_free_gpu_cell = if free_gpus > 0 {
-                        free_gpu_cell.style(Style::default().fg(Color::Green))
-                    } else {
-                        free_gpu_cell
-                    };
-
-                    let styled_free_cpu_cell = if free_cpus > 0 {
-                        free_cpu_cell.style(Style::default().fg(Color::Green))
-                    } else {
-                        free_cpu_cell
-                    };
-
-                    table_rows.push(Row::new(vec![
-                        partition_cell,
-                        name_cell,
-                        styled_free_gpu_cell,
-                        alloc_gpu_cell,
-                        total_gpu_cell,
-                        cpu_usage_cell,
-                        styled_free_cpu_cell,
-                    ]));
-                }
-            }
-
-            let displayed_rows: Vec<(usize, Row)> = table_rows
-                .into_iter()
+            let displayed_rows: Vec<(usize, Row)> = node_table
+                .rows
+                .iter()
+                .cloned()
                 .enumerate()
                 .skip(scroll)
                 .take(rows_per_page)
@@ -300,67 +547,308 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 } else {
                     Color::Rgb(40, 40, 40)
                 };
-                row = row.style(Style::default().bg(bg_color));
+                row = if index == selected {
+                    row.style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+                } else {
+                    row.style(Style::default().bg(bg_color))
+                };
                 row
             });
 
-            let header_cells = ["Partitions", "Node", "Free GPUs", "Alloc GPUs", "Total GPUs", "CPU Usage", "Free CPUs"]
-                .iter()
-                .map(|h| Cell::from(*h).style(Style::default().add_modifier(Modifier::BOLD)));
+            // The sort column is underlined; the columns are too narrow for an arrow.
+            let header_cells = view::headers(show_cluster).into_iter().map(|h| {
+                let style = Style::default().add_modifier(Modifier::BOLD);
+                if options.sort.is_some_and(|key| key.header() == h) {
+                    Cell::from(h).style(style.add_modifier(Modifier::UNDERLINED))
+                } else {
+                    Cell::from(h).style(style)
+                }
+            });
             let header = Row::new(header_cells)
                 .style(Style::default().fg(Color::Yellow));
 
+            let mut block_title = Vec::new();
+            if refresh_error.is_some() {
+                block_title.push(Span::styled(
+                    format!(" STALE: data from {} ", last_update.format("%H:%M:%S")),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ));
+            }
+            let search_title = match (&search, &options.node_pattern) {
+                (Some(text), _) => Some(format!(" /{}_ ", text)),
+                (None, Some(pattern)) => Some(format!(" /{} ", pattern.as_str())),
+                (None, None) => None,
+            };
+            if let Some(search_title) = search_title {
+                block_title.push(Span::styled(search_title, Style::default().fg(Color::Yellow)));
+            }
+            let table_block = Block::default().borders(Borders::ALL).title(Spans::from(block_title));
             let table = Table::new(rows)
                 .header(header)
-                .block(Block::default().borders(Borders::ALL))
-                .widths(&[
-                    Constraint::Length(20),
-                    Constraint::Length(15),
-                    Constraint::Length(10),
-                    Constraint::Length(10),
-                    Constraint::Length(10),
-                    Constraint::Length(10),
-                    Constraint::Length(10),
-                ])
+                .block(table_block)
+                .widths(&widths)
                 .column_spacing(1);
 
-            f.render_widget(table, layout[0]);
+            let show_cluster_tab_column = clusters.len() > 1 && options.cluster.is_none();
+            match tab {
+                Tab::Nodes => f.render_widget(table, layout[1]),
+                Tab::Jobs => tabs::draw_jobs(f, layout[1], &cluster_jobs, job_scroll, show_cluster_tab_column),
+                Tab::Partitions => tabs::draw_partitions(
+                    f,
+                    layout[1],
+                    &cluster_partitions,
+                    &nodes,
+                    &options,
+                    partition_scroll,
+                    show_cluster_tab_column,
+                ),
+                Tab::Reservations => tabs::draw_reservations(
+                    f,
+                    layout[1],
+                    &current_reservations,
+                    reservation_scroll,
+                    show_cluster_tab_column,
+                ),
+            }
+
+            let cluster_nodes = nodes.iter().filter(|node| options.includes_cluster(&node.cluster));
+            let summary_spans: Vec<Span> = summarize_gpu_types(cluster_nodes, options.count_unavailable)
+                .into_iter()
+                .flat_map(|(gpu_type, free_gpus, total_gpus)| {
+                    let free_style = if free_gpus > 0 {
+                        Style::default().fg(Color::Green)
+                    } else {
+                        Style::default().fg(Color::Red)
+                    };
+                    vec![
+                        Span::styled(format!(" {}: ", gpu_type), Style::default().add_modifier(Modifier::BOLD)),
+                        Span::styled(free_gpus.to_string(), free_style),
+                        Span::raw(format!("/{} free ", total_gpus)),
+                    ]
+                })
+                .collect();
+            let status_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(40)].as_ref())
+                .split(layout[3]);
+            f.render_widget(Paragraph::new(Spans::from(summary_spans)), status_layout[0]);
+
+            let status = if refreshing {
+                Span::styled("refreshing… ", Style::default().fg(Color::Yellow))
+            } else if refresh_error.is_some() {
+                Span::styled(
+                    format!("stale since {} ", last_update.format("%H:%M:%S")),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )
+            } else {
+                Span::styled(
+                    format!("updated {} ", last_update.format("%H:%M:%S")),
+                    Style::default().fg(Color::DarkGray),
+                )
+            };
+            f.render_widget(Paragraph::new(Spans::from(status)).alignment(Alignment::Right), status_layout[1]);
+
+            if let Some((error, retry_at)) = &refresh_error {
+                let age = (chrono::Local::now() - last_update).num_seconds();
+                let retry_in = retry_at.saturating_duration_since(Instant::now()).as_secs();
+                let message = format!(
+                    " Refresh failed: {}. Showing data from {} ago, retrying in {}s",
+                    error.lines().next().unwrap_or_default(),
+                    if age < 60 { format!("{}s", age) } else { time::format_duration(age) },
+                    retry_in
+                );
+                f.render_widget(
+                    Paragraph::new(message).style(Style::default().fg(Color::White).bg(Color::Red)),
+                    layout[4],
+                );
+            } else if !warnings.is_empty() {
+                f.render_widget(
+                    Paragraph::new(format!(" {}", warnings.join("; "))).style(Style::default().fg(Color::Black).bg(Color::Yellow)),
+                    layout[4],
+                );
+            }
+
+            if show_details && tab == Tab::Nodes {
+                if let Some((_, details)) = node_table.details.as_ref().filter(|(row, _)| *row == selected) {
+                    let node = &nodes[node_table.row_nodes[selected].unwrap_or_default()];
+                    detail_lines = details.len();
+                    let detail_block = Block::default()
+                        .title(format!("{} (PgUp/PgDn to scroll, Enter or Esc to close)", node.name))
+                        .borders(Borders::ALL);
+                    f.render_widget(
+                        Paragraph::new(details.clone())
+                            .block(detail_block)
+                            .wrap(Wrap { trim: false })
+                            .scroll((detail_scroll, 0)),
+                        layout[2],
+                    );
+                }
+            }
         })?;
 
         if event::poll(Duration::from_millis(100))? {
-            match event::read()? {
-                Event::Key(key_event) => match key_event.code {
+            match (&mut search, event::read()?) {
+                (_, Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column, row, .. })) => {
+                    // The header is the first line inside the table border.
+                    let table_area = layout[1];
+                    let clicked = (tab == Tab::Nodes && row == table_area.y + 1)
+                        .then(|| column_at(table_area.x + 1, table_area.width.saturating_sub(2), &widths, column))
+                        .flatten()
+                        .and_then(|index| view::headers(show_cluster).get(index).copied())
+                        .and_then(SortKey::from_header);
+                    if let Some(key) = clicked {
+                        if options.sort == Some(key) {
+                            options.sort_descending = !options.sort_descending;
+                        } else {
+                            options.sort = Some(key);
+                            options.sort_descending = false;
+                        }
+                        scroll = 0;
+                        selected = 0;
+                    }
+                }
+                (Some(text), Event::Key(key_event)) => {
+                    match key_event.code {
+                        KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => break,
+                        KeyCode::Enter => search = None,
+                        KeyCode::Esc => {
+                            search = None;
+                            options.node_pattern = None;
+                        }
+                        KeyCode::Backspace => {
+                            text.pop();
+                        }
+                        KeyCode::Char(c) => text.push(c),
+                        _ => {}
+                    }
+                    if let Some(text) = &search {
+                        options.node_pattern = (!text.trim().is_empty()).then(|| hostlist::Pattern::new(text));
+                    }
+                    scroll = 0;
+                    selected = 0;
+                }
+                (None, Event::Key(key_event)) => match key_event.code {
                     KeyCode::Char('q') => break,
+                    // Raw mode turns Ctrl-C into a key press instead of SIGINT.
+                    KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => break,
                     KeyCode::Char('f') => {
-                        hide_no_free_gpus = !hide_no_free_gpus;
+                        options.hide_no_free_gpus = !options.hide_no_free_gpus;
                         scroll = 0;
+                        selected = 0;
                     }
                     KeyCode::Char('s') => {
-                        group_by_partitions = !group_by_partitions;
+                        options.group_by_partitions = !options.group_by_partitions;
                         scroll = 0;
+                        selected = 0;
                     }
                     KeyCode::Char('c') => {
-                        gpu_only_mode = !gpu_only_mode;
+                        options.gpu_only_mode = !options.gpu_only_mode;
+                    }
+                    KeyCode::Char('d') => {
+                        options.count_unavailable = !options.count_unavailable;
+                    }
+                    KeyCode::Char('t') => {
+                        let gpu_types: Vec<String> = summarize_gpu_types(&nodes, options.count_unavailable)
+                            .into_iter()
+                            .map(|(gpu_type, _, _)| gpu_type)
+                            .collect();
+                        let next = match &options.gpu_type {
+                            Some(current) => gpu_types.iter().position(|t| t == current).map_or(0, |i| i + 1),
+                            None => 0,
+                        };
+                        options.gpu_type = gpu_types.get(next).cloned();
+                        scroll = 0;
+                        selected = 0;
+                    }
+                    KeyCode::Char('/') if tab == Tab::Nodes => {
+                        search = Some(options.node_pattern.as_ref().map_or(String::new(), |pattern| pattern.as_str().to_string()));
+                    }
+                    KeyCode::Char('o') => {
+                        options.sort = SortKey::cycle(options.sort);
+                        scroll = 0;
+                        selected = 0;
+                    }
+                    KeyCode::Char('r') => {
+                        options.sort_descending = !options.sort_descending;
+                        scroll = 0;
+                        selected = 0;
+                    }
+                    KeyCode::Char('a') => {
+                        options.allowed_only = !options.allowed_only;
+                        (nodes, partitions) = shown_nodes_and_partitions(&all_nodes, &all_partitions, &options);
+                        scroll = 0;
+                        selected = 0;
+                        partition_scroll = 0;
+                    }
+                    KeyCode::Char('g') => {
+                        options.group_by_clusters = !options.group_by_clusters;
+                        scroll = 0;
+                        selected = 0;
+                    }
+                    KeyCode::Char('m') if clusters.len() > 1 => {
+                        let next = match &options.cluster {
+                            Some(current) => clusters.iter().position(|c| c == current).map_or(0, |i| i + 1),
+                            None => 0,
+                        };
+                        options.cluster = clusters.get(next).cloned();
+                        scroll = 0;
+                        selected = 0;
+                        job_scroll = 0;
+                        partition_scroll = 0;
+                        reservation_scroll = 0;
+                    }
+                    KeyCode::Tab => {
+                        tab = tab.next();
+                    }
+                    KeyCode::Up | KeyCode::Char('k') if tab == Tab::Partitions => {
+                        partition_scroll = partition_scroll.saturating_sub(1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') if tab == Tab::Partitions => {
+                        partition_scroll = min(partition_scroll + 1, cluster_partitions.len().saturating_sub(rows_per_page));
+                    }
+                    KeyCode::Up | KeyCode::Char('k') if tab == Tab::Reservations => {
+                        reservation_scroll = reservation_scroll.saturating_sub(1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') if tab == Tab::Reservations => {
+                        reservation_scroll = min(reservation_scroll + 1, current_reservations.len().saturating_sub(rows_per_page));
+                    }
+                    KeyCode::Up | KeyCode::Char('k') if tab == Tab::Jobs => {
+                        job_scroll = job_scroll.saturating_sub(1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') if tab == Tab::Jobs => {
+                        job_scroll = min(job_scroll + 1, active_jobs.saturating_sub(rows_per_page));
+                    }
+                    KeyCode::Enter => {
+                        show_details = !show_details;
+                        detail_scroll = 0;
+                    }
+                    KeyCode::Esc => {
+                        show_details = false;
+                    }
+                    KeyCode::PageUp if show_details && tab == Tab::Nodes => {
+                        detail_scroll = detail_scroll.saturating_sub(layout[2].height.saturating_sub(3).max(1));
+                    }
+                    KeyCode::PageDown if show_details && tab == Tab::Nodes => {
+                        let last_line = u16::try_from(detail_lines.saturating_sub(1)).unwrap_or(u16::MAX);
+                        detail_scroll = min(detail_scroll.saturating_add(layout[2].height.saturating_sub(3).max(1)), last_line);
                     }
                     KeyCode::Up | KeyCode::Char('k') => {
-                        scroll = scroll.saturating_sub(1);
+                        selected = nearest_node_row(row_nodes, selected.saturating_sub(1), false);
                     }
                     KeyCode::Down | KeyCode::Char('j') => {
-                        let max_scroll = nodes.len().saturating_sub(rows_per_page);
-                        scroll = min(scroll + 1, max_scroll);
+                        selected = nearest_node_row(row_nodes, min(selected + 1, total_rows.saturating_sub(1)), true);
                     }
                     KeyCode::PageUp => {
-                        scroll = scroll.saturating_sub(rows_per_page);
+                        selected = nearest_node_row(row_nodes, selected.saturating_sub(rows_per_page), false);
                     }
                     KeyCode::PageDown => {
-                        let max_scroll = nodes.len().saturating_sub(rows_per_page);
-                        scroll = min(scroll + rows_per_page, max_scroll);
+                        selected = nearest_node_row(row_nodes, min(selected + rows_per_page, total_rows.saturating_sub(1)), true);
                     }
                     KeyCode::Home => {
-                        scroll = 0;
+                        selected = nearest_node_row(row_nodes, 0, true);
                     }
                     KeyCode::End => {
-                        scroll = nodes.len().saturating_sub(rows_per_page);
+                        selected = nearest_node_row(row_nodes, total_rows.saturating_sub(1), false);
                     }
                     _ => {}
                 },
@@ -368,15 +856,65 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        if last_refresh.elapsed() >= refresh_interval {
-            nodes = load_nodes_from_command()?;
-            last_refresh = Instant::now();
+        loop {
+            match refresh_events.try_recv() {
+                Ok(RefreshEvent::Started) => refreshing = true,
+                Ok(RefreshEvent::Loaded(snapshot)) => {
+                    refreshing = false;
+                    Snapshot { nodes: all_nodes, partitions: all_partitions, jobs, reservations, warnings } = snapshot;
+                    (nodes, partitions) = shown_nodes_and_partitions(&all_nodes, &all_partitions, &options);
+                    cached_table = None;
+                    refresh_error = None;
+                    last_update = chrono::Local::now();
+                }
+                // Keep showing the last good snapshot.
+                Ok(RefreshEvent::Failed { error, retry_in }) => {
+                    refreshing = false;
+                    refresh_error = Some((error, Instant::now() + retry_in));
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if refresh_error.is_none() {
+                        refresh_error = Some(("the refresh thread stopped".to_string(), Instant::now()));
+                    }
+                    refreshing = false;
+                    break;
+                }
+            }
         }
     }
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::buffer::Buffer;
+    use tui::widgets::Widget;
+
+    /// Fills every column of the node table with its index as a letter, draws it as tui does and
+    /// checks that `column_at` names the column drawn under each screen column.
+    #[test]
+    fn finds_the_clicked_column_where_tui_draws_it() {
+        for show_cluster in [false, true] {
+            let widths = node_table_widths(show_cluster);
+            let letters: Vec<String> = (0..widths.len())
+                .map(|index| char::from(b'a' + index as u8).to_string().repeat(40))
+                .collect();
+            for width in [300, 240, 120, 80, 33] {
+                let area = Rect { x: 1, y: 0, width, height: 1 };
+                let mut buffer = Buffer::empty(area);
+                Table::new(Vec::<Row>::new())
+                    .header(Row::new(letters.clone()))
+                    .widths(&widths)
+                    .column_spacing(1)
+                    .render(area, &mut buffer);
+                for x in area.x..area.x + width {
+                    let drawn = buffer.get(x, 0).symbol.as_bytes()[0];
+                    let expected = drawn.is_ascii_lowercase().then(|| usize::from(drawn - b'a'));
+                    assert_eq!(column_at(area.x, width, &widths, x), expected, "x = {} of {}", x, width);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::jobs::{self, Job};
+use crate::partition::Partition;
+use crate::view::{self, NodeView, ViewOptions};
+use crate::{extract_gpu_info, state, summarize_gpu_types, Node};
+use serde::Serialize;
+use std::error::Error;
+
+/// Prints the node table as aligned plain text, with the same filters and grouping as the TUI.
+pub fn print_table(nodes: &[Node], partitions: &[Partition], jobs: &[Job], options: &ViewOptions) {
+    let jobs_by_node = jobs::jobs_by_node(jobs);
+    let views = view::filter_nodes(nodes, partitions, &jobs_by_node, options);
+    let show_cluster = options.show_cluster_column(nodes);
+    let text_row = |view: &NodeView, show_partitions: bool| -> Vec<String> {
+        view.columns(show_partitions, show_cluster)
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect()
+    };
+
+    let headers = view::headers(show_cluster);
+    let mut rows: Vec<Vec<String>> = vec![headers.iter().map(|h| h.to_string()).collect()];
+    if options.is_grouped() {
+        for (group_name, views_in_group) in view::group_nodes(&views, options) {
+            rows.push(vec![group_name]);
+            rows.extend(views_in_group.into_iter().map(|view| text_row(view, !options.group_by_partitions)));
+        }
+    } else {
+        rows.extend(views.iter().map(|view| text_row(view, true)));
+    }
+
+    let mut widths = vec![0; headers.len()];
+    for row in &rows {
+        for (width, text) in widths.iter_mut().zip(row) {
+            *width = (*width).max(text.chars().count());
+        }
+    }
+
+    for row in &rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(text, width)| format!("{:<width$}", text, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+
+    let summary: Vec<String> = summarize_gpu_types(nodes, options.count_unavailable)
+        .into_iter()
+        .map(|(gpu_type, free_gpus, total_gpus)| format!("{}: {}/{} free", gpu_type, free_gpus, total_gpus))
+        .collect();
+    if !summary.is_empty() {
+        println!();
+        println!("{}", summary.join("  "));
+    }
+}
+
+/// One node as exported by `--format json|csv`, with the numbers the TUI shows.
+#[derive(Serialize)]
+struct NodeRecord<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cluster: Option<&'a str>,
+    partitions: &'a [String],
+    state: String,
+    available: bool,
+    reason: String,
+    gpu_types: Vec<GpuTypeRecord>,
+    free_gpus: u32,
+    /// Unix time at which the next GPU is expected to free up, when none is free now.
+    next_free_at: Option<i64>,
+    /// Longest job that could start now, in minutes; `null` when unlimited.
+    max_walltime_minutes: Option<i64>,
+    usable_gpus: u32,
+    alloc_gpus: u32,
+    total_gpus: u32,
+    cpus: u32,
+    alloc_cpus: u32,
+    free_cpus: u32,
+    /// Memory figures are in megabytes.
+    real_memory: u64,
+    alloc_memory: u64,
+    free_memory: u64,
+    os_free_memory: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct GpuTypeRecord {
+    #[serde(rename = "type")]
+    gpu_type: String,
+    alloc: u32,
+    total: u32,
+}
+
+impl<'a> NodeRecord<'a> {
+    fn new(view: &NodeView<'a>) -> Self {
+        let node = view.node;
+        NodeRecord {
+            name: &node.name,
+            cluster: node.cluster.as_deref(),
+            partitions: &node.partitions,
+            state: state::label(&view.states),
+            available: !view.is_unavailable,
+            reason: view.reason(),
+            gpu_types: view
+                .gpu_types
+                .iter()
+                .map(|gpu_type| {
+                    let (alloc, total) = extract_gpu_info(node, Some(gpu_type));
+                    GpuTypeRecord { gpu_type: gpu_type.clone(), alloc, total }
+                })
+                .collect(),
+            free_gpus: view.free_gpus,
+            next_free_at: view.next_free_at,
+            max_walltime_minutes: view.max_walltime.map(|seconds| seconds / 60),
+            usable_gpus: view.usable_gpus,
+            alloc_gpus: view.alloc_gpus,
+            total_gpus: view.total_gpus,
+            cpus: node.cpus,
+            alloc_cpus: node.alloc_cpus,
+            free_cpus: view.free_cpus,
+            real_memory: node.real_memory,
+            alloc_memory: node.alloc_memory,
+            free_memory: view.free_memory,
+            os_free_memory: node.free_mem,
+        }
+    }
+}
+
+/// Prints the filtered nodes as a JSON array. Grouping does not apply; every record lists its partitions.
+pub fn print_json(
+    nodes: &[Node],
+    partitions: &[Partition],
+    jobs: &[Job],
+    options: &ViewOptions,
+) -> Result<(), Box<dyn Error>> {
+    let jobs_by_node = jobs::jobs_by_node(jobs);
+    let views = view::filter_nodes(nodes, partitions, &jobs_by_node, options);
+    let records: Vec<NodeRecord> = views.iter().map(NodeRecord::new).collect();
+    println!("{}", serde_json::to_string_pretty(&records)?);
+    Ok(())
+}
+
+/// In the order of the table columns.
+const CSV_HEADERS: [&str; 19] = [
+    "cluster", "name", "partitions", "state", "available", "reason", "gpu_types", "free_gpus", "next_free_at",
+    "max_walltime_minutes", "usable_gpus", "alloc_gpus", "total_gpus", "cpus", "alloc_cpus", "free_cpus", "real_memory_mb",
+    "alloc_memory_mb", "free_memory_mb",
+];
+
+/// Prints the filtered nodes as CSV, one row per node. List fields are joined with `;`
+/// and times are Unix timestamps.
+pub fn print_csv(
+    nodes: &[Node],
+    partitions: &[Partition],
+    jobs: &[Job],
+    options: &ViewOptions,
+) -> Result<(), Box<dyn Error>> {
+    let jobs_by_node = jobs::jobs_by_node(jobs);
+    println!("{}", CSV_HEADERS.join(","));
+    for view in view::filter_nodes(nodes, partitions, &jobs_by_node, options) {
+        let node = view.node;
+        let fields = [
+            node.cluster.clone().unwrap_or_default(),
+            node.name.clone(),
+            node.partitions.join(";"),
+            state::label(&view.states),
+            (!view.is_unavailable).to_string(),
+            view.reason(),
+            view.gpu_types.join(";"),
+            view.free_gpus.to_string(),
+            view.next_free_at.map(|time| time.to_string()).unwrap_or_default(),
+            view.max_walltime.map(|seconds| (seconds / 60).to_string()).unwrap_or_default(),
+            view.usable_gpus.to_string(),
+            view.alloc_gpus.to_string(),
+            view.total_gpus.to_string(),
+            node.cpus.to_string(),
+            node.alloc_cpus.to_string(),
+            view.free_cpus.to_string(),
+            node.real_memory.to_string(),
+            node.alloc_memory.to_string(),
+            view.free_memory.to_string(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_escape(field)).collect();
+        println!("{}", fields.join(","));
+    }
+    Ok(())
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
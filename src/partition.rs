@@ -0,0 +1,250 @@
+use crate::view::ViewOptions;
+use crate::{extract_free_resources, extract_gpu_info, schema, state, Node};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct PartitionOutput {
+    #[serde(default)]
+    pub partitions: Vec<Partition>,
+}
+
+/// A partition as `scontrol show partitions` describes it, whichever format it came in.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(from = "RawPartition")]
+pub struct Partition {
+    pub name: String,
+    /// The cluster the partition was loaded from with `--clusters`, `None` otherwise.
+    pub cluster: Option<String>,
+    /// The partition's JobDefaults, e.g. `DefCpuPerGPU=8,DefMemPerGPU=65536`.
+    pub job_defaults: Option<String>,
+    /// `UP`, `DOWN`, `DRAIN` or `INACTIVE`.
+    pub state: Vec<String>,
+    /// MaxTime in minutes, `None` when unlimited.
+    pub max_time: Option<u64>,
+    /// DefaultTime in minutes, `None` when not set.
+    pub default_time: Option<u64>,
+    pub priority_tier: u32,
+    /// The partition QOS, not the QOS jobs may request.
+    pub qos: Option<String>,
+    /// AllowAccounts, `None` when all accounts are allowed.
+    pub allow_accounts: Option<Vec<String>>,
+    pub deny_accounts: Vec<String>,
+    /// AllowGroups, `None` when all groups are allowed.
+    pub allow_groups: Option<Vec<String>>,
+    /// AllowQos, `None` when all QOS are allowed.
+    pub allow_qos: Option<Vec<String>>,
+    pub deny_qos: Vec<String>,
+    /// Whether the user may submit here, as far as the source could tell. Set by the source
+    /// with `allows`; `true` when the user's accounts and groups are unknown.
+    pub allowed: bool,
+}
+
+/// Who the user is, for the partitions they may submit to and the reservations they are in.
+/// `None` fields could not be looked up and are not checked.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Access {
+    pub user: Option<String>,
+    pub accounts: Option<Vec<String>>,
+    pub groups: Option<Vec<String>>,
+    pub qos: Option<Vec<String>>,
+}
+
+/// The partition JSON of every supported data_parser version. Up to v0.0.38 the fields are
+/// flat (`max_time_limit`, `allowed_accounts`, `qos` as a string); from v0.0.39 on they are
+/// nested objects.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawPartition {
+    name: String,
+    defaults: RawDefaults,
+    maximums: RawMaximums,
+    priority: RawPriority,
+    partition: RawState,
+    accounts: Value,
+    groups: Value,
+    qos: Value,
+    state: Value,
+    max_time_limit: Value,
+    default_time_limit: Value,
+    priority_tier: Value,
+    allowed_accounts: Value,
+    denied_accounts: Value,
+    allowed_groups: Value,
+    allowed_qos: Value,
+    denied_qos: Value,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawDefaults {
+    job: Option<String>,
+    time: Value,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawMaximums {
+    time: Value,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawPriority {
+    tier: Value,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawState {
+    #[serde(deserialize_with = "state::deserialize_state")]
+    state: Vec<String>,
+}
+
+impl From<RawPartition> for Partition {
+    fn from(raw: RawPartition) -> Self {
+        let text = |value: &Value| value.as_str().filter(|s| !s.is_empty()).map(str::to_string);
+        let state = if raw.partition.state.is_empty() {
+            text(&raw.state).map(|state| state::normalize(vec![state])).unwrap_or_default()
+        } else {
+            raw.partition.state
+        };
+        Partition {
+            name: raw.name,
+            cluster: None,
+            job_defaults: raw.defaults.job.filter(|job| !job.is_empty()),
+            state,
+            max_time: minutes(&raw.maximums.time).or_else(|| minutes(&raw.max_time_limit)),
+            default_time: minutes(&raw.defaults.time).or_else(|| minutes(&raw.default_time_limit)),
+            priority_tier: schema::number(&raw.priority.tier)
+                .or_else(|| schema::number(&raw.priority_tier))
+                .unwrap_or(0) as u32,
+            qos: text(&raw.qos).or_else(|| text(&raw.qos["assigned"])),
+            allow_accounts: allow_list(text(&raw.accounts["allowed"]).or_else(|| text(&raw.allowed_accounts))),
+            deny_accounts: split_list(text(&raw.accounts["deny"]).or_else(|| text(&raw.denied_accounts))),
+            allow_groups: allow_list(text(&raw.groups["allowed"]).or_else(|| text(&raw.allowed_groups))),
+            allow_qos: allow_list(text(&raw.qos["allowed"]).or_else(|| text(&raw.allowed_qos))),
+            deny_qos: split_list(text(&raw.qos["deny"]).or_else(|| text(&raw.denied_qos))),
+            allowed: true,
+        }
+    }
+}
+
+/// An AllowAccounts, AllowGroups or AllowQos list. Missing, empty and `ALL` allow everyone.
+pub fn allow_list(list: Option<String>) -> Option<Vec<String>> {
+    let list = split_list(list);
+    if list.is_empty() || list.iter().any(|entry| entry == "ALL") {
+        None
+    } else {
+        Some(list)
+    }
+}
+
+/// A comma separated list such as DenyAccounts, empty when missing.
+pub fn split_list(list: Option<String>) -> Vec<String> {
+    list.iter()
+        .flat_map(|list| list.split(','))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// A time limit in minutes. Slurm marks unlimited and unset with `infinite`, or with
+/// INFINITE/NO_VAL (`0xffffffff`/`0xfffffffe`) before number objects existed.
+fn minutes(value: &Value) -> Option<u64> {
+    schema::number(value)
+        .filter(|minutes| (0..0xffff_fffe).contains(minutes))
+        .map(|minutes| minutes as u64)
+}
+
+/// The partition called `name` on `node`'s cluster.
+pub fn find<'a>(partitions: &'a [Partition], node: &Node, name: &str) -> Option<&'a Partition> {
+    partitions
+        .iter()
+        .find(|partition| partition.name == name && partition.cluster == node.cluster)
+}
+
+impl Partition {
+    pub fn def_cpu_per_gpu(&self) -> Option<u32> {
+        self.job_default("DefCpuPerGPU")?.parse().ok()
+    }
+
+    /// DefMemPerGPU in megabytes.
+    pub fn def_mem_per_gpu(&self) -> Option<u64> {
+        self.job_default("DefMemPerGPU")?.parse().ok()
+    }
+
+    /// The CPUs and megabytes of memory a job gets per GPU unless it asks otherwise:
+    /// DefCpuPerGPU and DefMemPerGPU, or at least one CPU and some memory when they are not set.
+    pub fn per_gpu_defaults(partition: Option<&Partition>) -> (u32, u64) {
+        let cpus = partition.and_then(Partition::def_cpu_per_gpu).unwrap_or(1).max(1);
+        let memory = partition.and_then(Partition::def_mem_per_gpu).unwrap_or(1).max(1);
+        (cpus, memory)
+    }
+
+    /// Whether jobs can start here. A partition of unknown state counts as up.
+    pub fn is_up(&self) -> bool {
+        self.state.is_empty() || self.state.iter().any(|state| state == "UP")
+    }
+
+    /// Whether someone with `access` may submit here. An account or QOS passes if it is
+    /// allowed and not denied; one passing account, group and QOS is enough.
+    pub fn allows(&self, access: &Access) -> bool {
+        let passes = |allow: &Option<Vec<String>>, deny: &[String], mine: &Option<Vec<String>>| match mine {
+            Some(mine) => mine
+                .iter()
+                .any(|entry| allow.as_ref().is_none_or(|allow| allow.contains(entry)) && !deny.contains(entry)),
+            None => true,
+        };
+        passes(&self.allow_accounts, &self.deny_accounts, &access.accounts)
+            && passes(&self.allow_groups, &[], &access.groups)
+            && passes(&self.allow_qos, &self.deny_qos, &access.qos)
+    }
+
+    fn job_default(&self, key: &str) -> Option<&str> {
+        self.job_defaults
+            .as_deref()?
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.trim())
+    }
+
+    /// The nodes of `nodes` in this partition.
+    pub fn member_nodes<'a>(&'a self, nodes: &'a [Node]) -> impl Iterator<Item = &'a Node> + 'a {
+        nodes
+            .iter()
+            .filter(|node| node.cluster == self.cluster && node.partitions.contains(&self.name))
+    }
+
+    /// Free and total GPUs over the partition's nodes, counted as the node view counts them.
+    pub fn gpus(&self, nodes: &[Node], options: &ViewOptions) -> (u32, u32) {
+        let gpu_type = options.gpu_type.as_deref();
+        self.member_nodes(nodes).fold((0, 0), |(free, total), node| {
+            let (_, node_total) = extract_gpu_info(node, gpu_type);
+            let (node_free, _) = extract_free_resources(node, gpu_type, options.count_unavailable);
+            (free + node_free, total + node_total)
+        })
+    }
+}
+
+/// `nodes` and `partitions` cut down to the partitions the user may submit to. Nodes lose the
+/// partitions they may not use and are left out when none remains.
+pub fn restrict_to_allowed(nodes: &[Node], partitions: &[Partition]) -> (Vec<Node>, Vec<Partition>) {
+    let nodes = nodes
+        .iter()
+        .filter_map(|node| {
+            let allowed: Vec<String> = node
+                .partitions
+                .iter()
+                .filter(|name| find(partitions, node, name).is_none_or(|partition| partition.allowed))
+                .cloned()
+                .collect();
+            (!allowed.is_empty()).then(|| Node { partitions: allowed, ..node.clone() })
+        })
+        .collect();
+    let partitions = partitions.iter().filter(|partition| partition.allowed).cloned().collect();
+    (nodes, partitions)
+}
@@ -0,0 +1,371 @@
+use crate::jobs::Job;
+use crate::partition::{self, Partition};
+use crate::reservation::Reservation;
+use crate::time::parse_timestamp;
+use crate::{gres, hostlist, state, Node};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// Fields of `scontrol show nodes -o` that have a name of their own in the JSON output,
+/// so the detail pane shows them the same way.
+const JSON_NAMES: [(&str, &str); 7] = [
+    ("NodeHostName", "hostname"),
+    ("AvailableFeatures", "features"),
+    ("ActiveFeatures", "active_features"),
+    ("Arch", "architecture"),
+    ("OS", "operating_system"),
+    ("CfgTRES", "tres"),
+    ("AllocTRES", "tres_used"),
+];
+
+/// Fields that `Node` keeps in its own members rather than in `extra`.
+const NODE_FIELDS: [&str; 12] = [
+    "NodeName", "Gres", "GresUsed", "Partitions", "CPUTot", "CPUAlloc", "RealMemory", "AllocMem", "FreeMem",
+    "State", "Reason", "BootTime",
+];
+
+/// The `squeue --format` that `parse_jobs` reads. The job name goes last since it may contain `|`.
+pub const SQUEUE_FORMAT: &str = "%A|%u|%P|%T|%N|%e|%b|%j";
+
+/// Parses `scontrol show nodes -o`, one node per line, as printed by Slurm builds without `--json`.
+pub fn parse_nodes(data: &str) -> Result<Vec<Node>, Box<dyn Error>> {
+    data.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_node)
+        .collect()
+}
+
+fn parse_node(line: &str) -> Result<Node, Box<dyn Error>> {
+    let fields = fields(line);
+    let name = fields.get("NodeName").ok_or_else(|| format!("No NodeName in scontrol output: {}", line))?;
+
+    let mut extra = BTreeMap::new();
+    for (key, value) in fields.iter() {
+        if NODE_FIELDS.contains(key) {
+            continue;
+        }
+        if let Some(value) = value {
+            let key = JSON_NAMES
+                .iter()
+                .find(|(plain, _)| plain == key)
+                .map_or(*key, |(_, json)| *json);
+            extra.insert(key.to_string(), Value::from(value.as_str()));
+        }
+    }
+    if let Some(boot_time) = fields.get("BootTime").as_deref().and_then(parse_timestamp) {
+        extra.insert("boot_time".to_string(), Value::from(boot_time));
+    }
+
+    // `Reason=bad gpu [root@2024-10-14T10:00:00]` carries who set it and when.
+    let mut reason = fields.get("Reason");
+    if let Some((text, set_by)) = reason.as_deref().and_then(split_reason) {
+        let (user, time) = set_by.split_once('@').unwrap_or((set_by, ""));
+        extra.insert("reason_set_by_user".to_string(), Value::from(user));
+        if let Some(time) = parse_timestamp(time) {
+            extra.insert("reason_changed_at".to_string(), Value::from(time));
+        }
+        reason = Some(text.to_string());
+    }
+
+    Ok(Node {
+        name,
+        gres: fields.get("Gres"),
+        gres_used: fields.get("GresUsed"),
+        partitions: fields.get("Partitions")
+            .map(|list| list.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+        cpus: number(fields.get("CPUTot")),
+        alloc_cpus: number(fields.get("CPUAlloc")),
+        state: fields.get("State").map(|state| parse_state(&state)).unwrap_or_default(),
+        state_flags: Vec::new(),
+        reason,
+        real_memory: number(fields.get("RealMemory")),
+        alloc_memory: number(fields.get("AllocMem")),
+        free_mem: fields.get("FreeMem").and_then(|value| value.parse().ok()),
+        cluster: None,
+        reservations: Vec::new(),
+        extra,
+    })
+}
+
+/// Parses `scontrol show partitions -o`, one partition per line.
+pub fn parse_partitions(data: &str) -> Result<Vec<Partition>, Box<dyn Error>> {
+    data.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields = fields(line);
+            Ok(Partition {
+                name: fields.get("PartitionName").ok_or_else(|| format!("No PartitionName in scontrol output: {}", line))?,
+                cluster: None,
+                job_defaults: fields.get("JobDefaults"),
+                state: fields.get("State").map(|state| state::normalize(vec![state])).unwrap_or_default(),
+                max_time: fields.get("MaxTime").as_deref().and_then(parse_minutes),
+                default_time: fields.get("DefaultTime").as_deref().and_then(parse_minutes),
+                priority_tier: number(fields.get("PriorityTier")),
+                qos: fields.get("QoS"),
+                allow_accounts: partition::allow_list(fields.get("AllowAccounts")),
+                deny_accounts: partition::split_list(fields.get("DenyAccounts")),
+                allow_groups: partition::allow_list(fields.get("AllowGroups")),
+                allow_qos: partition::allow_list(fields.get("AllowQos")),
+                deny_qos: partition::split_list(fields.get("DenyQos")),
+                allowed: true,
+            })
+        })
+        .collect()
+}
+
+/// Parses `scontrol show reservations -o`, one reservation per line. Lines without a
+/// ReservationName, such as `No reservations in the system`, are skipped.
+pub fn parse_reservations(data: &str) -> Result<Vec<Reservation>, Box<dyn Error>> {
+    Ok(data
+        .lines()
+        .filter_map(|line| {
+            let fields = fields(line);
+            Some(Reservation {
+                name: fields.get("ReservationName")?,
+                cluster: None,
+                node_list: fields.get("Nodes").unwrap_or_default(),
+                start_time: fields.get("StartTime").as_deref().and_then(parse_timestamp),
+                end_time: fields.get("EndTime").as_deref().and_then(parse_timestamp),
+                users: partition::split_list(fields.get("Users")),
+                accounts: partition::split_list(fields.get("Accounts")),
+                groups: partition::split_list(fields.get("Groups")),
+                flags: partition::split_list(fields.get("Flags")),
+                admits_me: false,
+            })
+        })
+        .collect())
+}
+
+/// Parses `sacctmgr -nP show associations format=account,qos` into the accounts and QOS
+/// the user may submit with. Either is `None` when no association lists any.
+pub fn parse_associations(data: &str) -> (Option<Vec<String>>, Option<Vec<String>>) {
+    let mut accounts = Vec::new();
+    let mut qos = Vec::new();
+    for line in data.lines() {
+        let mut columns = line.split('|');
+        accounts.extend(columns.next().and_then(null_if_empty));
+        qos.extend(partition::split_list(columns.next().and_then(null_if_empty)));
+    }
+    accounts.sort();
+    accounts.dedup();
+    qos.sort();
+    qos.dedup();
+    let known = |list: Vec<String>| (!list.is_empty()).then_some(list);
+    (known(accounts), known(qos))
+}
+
+/// Parses `squeue --noheader --format=SQUEUE_FORMAT`.
+///
+/// squeue only prints GPUs requested per node (`%b`), so jobs that asked for `--gpus` in
+/// total show up without GPUs.
+pub fn parse_jobs(data: &str) -> Result<Vec<Job>, Box<dyn Error>> {
+    data.lines()
+        // `squeue -M` heads each cluster's jobs with `CLUSTER: name`, even with --noheader.
+        .filter(|line| !line.trim().is_empty() && !line.starts_with("CLUSTER: "))
+        .map(|line| {
+            let columns: Vec<&str> = line.trim().splitn(8, '|').collect();
+            let [job_id, user_name, partition, job_state, nodes, end_time, gres_per_node, name] = columns[..] else {
+                return Err(format!("Unexpected squeue output: {}", line).into());
+            };
+            let nodes = null_if_empty(nodes).unwrap_or_default();
+            let node_count = hostlist::expand(&nodes).len();
+
+            // `%b` reads `gres:gpu:a100:2` or `gres/gpu:2` depending on the release.
+            let per_node = gres_per_node
+                .split(',')
+                .map(|entry| entry.trim_start_matches("gres:").trim_start_matches("gres/"))
+                .collect::<Vec<_>>()
+                .join(",");
+            let gpus_per_node = gres::gpu_count(&gres::parse(&per_node), None);
+            let (tres_alloc_str, gres_detail) = if gpus_per_node > 0 && node_count > 0 {
+                (
+                    format!("gres/gpu={}", gpus_per_node * node_count as u32),
+                    vec![format!("gpu:{}", gpus_per_node); node_count],
+                )
+            } else {
+                (String::new(), Vec::new())
+            };
+
+            Ok(Job {
+                job_id: job_id.parse().map_err(|_| format!("Unexpected squeue job id: {}", job_id))?,
+                user_name: user_name.to_string(),
+                name: name.to_string(),
+                partition: partition.to_string(),
+                job_state: state::normalize(vec![job_state.to_string()]),
+                nodes,
+                end_time: parse_timestamp(end_time).map_or(Value::Null, Value::from),
+                tres_alloc_str,
+                gres_detail,
+                cluster: None,
+            })
+        })
+        .collect()
+}
+
+/// Splits one `-o` record into `Key=value` fields. Values may contain spaces (e.g. `OS`),
+/// so a word only starts a new field when it looks like `Key=`. `Reason` runs to the end
+/// of the line, as scontrol prints it last. Empty, `(null)` and `N/A` values are `None`.
+fn fields(line: &str) -> Fields<'_> {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    for word in line.split_whitespace() {
+        let reason_started = fields.last().is_some_and(|(key, _)| *key == "Reason");
+        match word.split_once('=') {
+            Some((key, value)) if !reason_started && is_key(key) => fields.push((key, value.to_string())),
+            _ => {
+                if let Some((_, value)) = fields.last_mut() {
+                    value.push(' ');
+                    value.push_str(word);
+                }
+            }
+        }
+    }
+    Fields(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key, null_if_empty(&value)))
+            .collect(),
+    )
+}
+
+/// The fields of one `-o` record, in the order scontrol prints them.
+struct Fields<'a>(Vec<(&'a str, Option<String>)>);
+
+impl<'a> Fields<'a> {
+    /// The value of `key`, `None` when it is missing or empty.
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.iter().find(|(name, _)| *name == key).and_then(|(_, value)| value.clone())
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &(&'a str, Option<String>)> {
+        self.0.iter()
+    }
+}
+
+fn is_key(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_uppercase()) && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn null_if_empty(value: &str) -> Option<String> {
+    match value.trim() {
+        "" | "(null)" | "N/A" | "n/a" => None,
+        value => Some(value.to_string()),
+    }
+}
+
+fn number<T: std::str::FromStr + Default>(value: Option<String>) -> T {
+    value.and_then(|value| value.parse().ok()).unwrap_or_default()
+}
+
+/// Parses a time limit such as `2-00:00:00`, `04:00:00` or `30` into minutes.
+/// `UNLIMITED` and `NONE` give `None`.
+fn parse_minutes(s: &str) -> Option<u64> {
+    let (days, clock) = match s.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, s),
+    };
+    let parts: Vec<u64> = clock.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let minutes = match parts[..] {
+        [hours, minutes, _seconds] => hours * 60 + minutes,
+        // Without days, Slurm reads `M:S`; with days, `D-H:M`.
+        [first, second] if days > 0 => first * 60 + second,
+        [minutes, _seconds] => minutes,
+        [minutes] if days == 0 => minutes,
+        [hours] => hours * 60,
+        _ => return None,
+    };
+    Some(days * 24 * 60 + minutes)
+}
+
+/// Splits `bad gpu [root@2024-10-14T10:00:00]` into the reason and `root@2024-10-14T10:00:00`.
+fn split_reason(reason: &str) -> Option<(&str, &str)> {
+    let (text, set_by) = reason.strip_suffix(']')?.rsplit_once(" [")?;
+    set_by.contains('@').then_some((text, set_by))
+}
+
+/// Plain-text states may carry a suffix, e.g. `IDLE*` for a node that is not responding.
+fn parse_state(state: &str) -> Vec<String> {
+    let mut states: Vec<String> = state.split('+').map(str::to_string).collect();
+    if let Some(first) = states.first_mut() {
+        if let Some(base) = first.strip_suffix('*') {
+            *first = base.to_string();
+            states.push("NOT_RESPONDING".to_string());
+        }
+    }
+    state::normalize(states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract_gpu_info, node_states};
+
+    #[test]
+    fn splits_fields() {
+        let line = "NodeName=gpu03 OS=Linux 5.14.0 #1 SMP Gres=(null) State=IDLE+DRAIN \
+                    Reason=swap GPU=3 with Spare=yes [root@2024-10-15T13:46:40]";
+        let expected = [
+            ("NodeName", Some("gpu03")),
+            ("OS", Some("Linux 5.14.0 #1 SMP")),
+            ("Gres", None),
+            ("State", Some("IDLE+DRAIN")),
+            ("Reason", Some("swap GPU=3 with Spare=yes [root@2024-10-15T13:46:40]")),
+        ];
+        let fields = fields(line);
+        let fields: Vec<(&str, Option<&str>)> = fields.iter().map(|(key, value)| (*key, value.as_deref())).collect();
+        assert_eq!(fields, expected);
+    }
+
+    #[test]
+    fn parses_time_limits() {
+        let cases = [
+            ("UNLIMITED", None),
+            ("NONE", None),
+            ("30", Some(30)),
+            ("30:00", Some(30)),
+            ("04:00:00", Some(240)),
+            ("1-12", Some(36 * 60)),
+            ("1-12:30", Some(36 * 60 + 30)),
+            ("2-00:00:00", Some(2 * 24 * 60)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_minutes(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parses_a_node_line() {
+        let line = "NodeName=gpu21 Arch=x86_64 CPUAlloc=8 CPUTot=32 Gres=gpu:4 GresUsed=gpu:1 \
+                    OS=Linux 5.14.0 RealMemory=128000 AllocMem=0 FreeMem=120000 State=DOWN* \
+                    Partitions=lab,gpu BootTime=None Reason=Not responding [root@2024-10-15T13:46:40]";
+        let node = parse_node(line).unwrap();
+        assert_eq!(node.name, "gpu21");
+        assert_eq!(extract_gpu_info(&node, None), (1, 4));
+        assert_eq!((node.cpus, node.alloc_cpus), (32, 8));
+        assert_eq!((node.real_memory, node.alloc_memory, node.free_mem), (128000, 0, Some(120000)));
+        assert_eq!(node.partitions, ["lab", "gpu"]);
+        assert_eq!(node.state, ["DOWN", "NOT_RESPONDING"]);
+        assert_eq!(node.reason.as_deref(), Some("Not responding"));
+        assert_eq!(node.extra["reason_set_by_user"], "root");
+        assert_eq!(node.extra["operating_system"], "Linux 5.14.0");
+        assert!(!node.extra.contains_key("boot_time"));
+    }
+
+    #[test]
+    fn reads_the_plain_text_fixture_like_the_json_one() {
+        let plain = parse_nodes(include_str!("../fixtures/schemas/scontrol-o.txt")).unwrap();
+        let json: crate::ScontrolOutput = serde_json::from_str(include_str!("../fixtures/nodes.json")).unwrap();
+        let summary = |nodes: &[Node]| -> Vec<String> {
+            nodes
+                .iter()
+                .map(|node| {
+                    let gpus = extract_gpu_info(node, None);
+                    format!("{} {:?} {:?} {} {}", node.name, gpus, node_states(node), node.alloc_cpus, node.alloc_memory)
+                })
+                .collect()
+        };
+        assert_eq!(summary(&plain), summary(&json.nodes));
+        assert_eq!(plain[2].reason.as_deref(), Some("bad gpu 3"));
+    }
+}
@@ -0,0 +1,45 @@
+use crate::source::{load_snapshot, NodeSource, Snapshot};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+/// The longest wait between retries while the source keeps failing.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Progress of the background refresh, as seen by the UI.
+pub enum RefreshEvent {
+    Started,
+    Loaded(Snapshot),
+    /// The load failed; the next attempt is made after `retry_in`.
+    Failed { error: String, retry_in: Duration },
+}
+
+/// Reloads `source` every `interval` on a background thread, so a slow scontrol never blocks
+/// the UI. Failed loads are retried with exponential backoff up to `MAX_BACKOFF`.
+/// The thread stops once the receiver is dropped.
+pub fn spawn(mut source: Box<dyn NodeSource + Send>, interval: Duration) -> Receiver<RefreshEvent> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut delay = interval;
+        loop {
+            thread::sleep(delay);
+            if sender.send(RefreshEvent::Started).is_err() {
+                return;
+            }
+            let event = match load_snapshot(source.as_mut()) {
+                Ok(snapshot) => {
+                    delay = interval;
+                    RefreshEvent::Loaded(snapshot)
+                }
+                Err(error) => {
+                    delay = (delay * 2).min(MAX_BACKOFF.max(interval));
+                    RefreshEvent::Failed { error: error.to_string(), retry_in: delay }
+                }
+            };
+            if sender.send(event).is_err() {
+                return;
+            }
+        }
+    });
+    receiver
+}
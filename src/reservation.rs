@@ -0,0 +1,175 @@
+use crate::partition::{split_list, Access};
+use crate::time::seconds_until;
+use crate::{hostlist, schema, Node};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ReservationOutput {
+    #[serde(default)]
+    pub reservations: Vec<Reservation>,
+}
+
+/// A reservation as `scontrol show reservations` describes it, whichever format it came in.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(from = "RawReservation")]
+pub struct Reservation {
+    pub name: String,
+    /// The cluster the reservation was loaded from with `--clusters`, `None` otherwise.
+    pub cluster: Option<String>,
+    /// Hostlist of the reserved nodes, e.g. `gpu[01-02]`.
+    pub node_list: String,
+    /// Unix times; `None` when not set or, for the end, never.
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    /// Users, accounts and groups allowed in. Users and accounts starting with `-` are the
+    /// ones kept out instead.
+    pub users: Vec<String>,
+    pub accounts: Vec<String>,
+    pub groups: Vec<String>,
+    /// e.g. `MAINT`, `IGNORE_JOBS` or `SPEC_NODES`.
+    pub flags: Vec<String>,
+    /// Whether the user may run jobs in the reservation. Set by the source with `admits`.
+    pub admits_me: bool,
+}
+
+/// The reservation JSON of every supported data_parser version. The user, account and group
+/// lists are comma separated strings throughout; flags are a list, or a string up to v0.0.38.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawReservation {
+    name: String,
+    node_list: String,
+    start_time: Value,
+    end_time: Value,
+    users: Value,
+    accounts: Value,
+    groups: Value,
+    flags: Value,
+}
+
+impl From<RawReservation> for Reservation {
+    fn from(raw: RawReservation) -> Self {
+        let list = |value: &Value| split_list(value.as_str().map(str::to_string));
+        let flags = match raw.flags.as_array() {
+            Some(flags) => flags.iter().filter_map(Value::as_str).map(str::to_uppercase).collect(),
+            None => list(&raw.flags),
+        };
+        Reservation {
+            name: raw.name,
+            cluster: None,
+            node_list: raw.node_list,
+            start_time: timestamp(&raw.start_time),
+            end_time: timestamp(&raw.end_time),
+            users: list(&raw.users),
+            accounts: list(&raw.accounts),
+            groups: list(&raw.groups),
+            flags,
+            admits_me: false,
+        }
+    }
+}
+
+/// A Unix time, `None` when unset or infinite.
+fn timestamp(value: &Value) -> Option<i64> {
+    schema::number(value).filter(|time| *time > 0)
+}
+
+impl Reservation {
+    pub fn node_names(&self) -> Vec<String> {
+        hostlist::expand(&self.node_list)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.start_time.is_none_or(|start| seconds_until(start) <= 0) && !self.has_ended()
+    }
+
+    pub fn has_ended(&self) -> bool {
+        self.end_time.is_some_and(|end| seconds_until(end) <= 0)
+    }
+
+    /// Whether someone with `access` may run jobs in the reservation. Every list the
+    /// reservation sets has to let them in, as Slurm requires; unknown accounts and groups
+    /// let no one in.
+    pub fn admits(&self, access: &Access) -> bool {
+        let user: Vec<String> = access.user.iter().cloned().collect();
+        let accounts = access.accounts.clone().unwrap_or_default();
+        let groups = access.groups.clone().unwrap_or_default();
+        let lists = [(&self.users, &user), (&self.accounts, &accounts), (&self.groups, &groups)];
+        lists.iter().any(|(list, _)| !list.is_empty()) && lists.iter().all(|(list, mine)| lets_in(list, mine))
+    }
+}
+
+/// Whether a reservation's user, account or group `list` lets in any of `mine`. A list of only
+/// `-name` entries lets in everyone else.
+fn lets_in(list: &[String], mine: &[String]) -> bool {
+    if list.is_empty() {
+        return true;
+    }
+    let excluded: Vec<&str> = list.iter().filter_map(|entry| entry.strip_prefix('-')).collect();
+    if excluded.len() == list.len() {
+        !mine.iter().any(|entry| excluded.contains(&entry.as_str()))
+    } else {
+        mine.iter().any(|entry| list.contains(entry))
+    }
+}
+
+/// A reservation as seen from one of its nodes.
+#[derive(Debug, Clone)]
+pub struct NodeReservation {
+    pub name: String,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub admits_me: bool,
+}
+
+impl NodeReservation {
+    pub fn is_active(&self) -> bool {
+        self.start_time.is_none_or(|start| seconds_until(start) <= 0)
+            && self.end_time.is_none_or(|end| seconds_until(end) > 0)
+    }
+}
+
+/// Attaches every reservation that has not ended to the nodes it covers.
+pub fn mark_nodes(nodes: &mut [Node], reservations: &[Reservation]) {
+    for reservation in reservations.iter().filter(|reservation| !reservation.has_ended()) {
+        let node_names: HashSet<String> = reservation.node_names().into_iter().collect();
+        for node in nodes
+            .iter_mut()
+            .filter(|node| node.cluster == reservation.cluster && node_names.contains(&node.name))
+        {
+            node.reservations.push(NodeReservation {
+                name: reservation.name.clone(),
+                start_time: reservation.start_time,
+                end_time: reservation.end_time,
+                admits_me: reservation.admits_me,
+            });
+        }
+    }
+}
+
+/// The active reservation keeping the user off `node`, if any.
+pub fn blocking(node: &Node) -> Option<&NodeReservation> {
+    node.reservations
+        .iter()
+        .find(|reservation| !reservation.admits_me && reservation.is_active())
+}
+
+/// When the next reservation on `node` that has yet to start begins.
+pub fn next_start(node: &Node) -> Option<i64> {
+    node.reservations
+        .iter()
+        .filter(|reservation| !reservation.is_active())
+        .filter_map(|reservation| reservation.start_time)
+        .min()
+}
+
+/// The next reservation on `node` the user is not part of, once it starts.
+pub fn upcoming(node: &Node) -> Option<&NodeReservation> {
+    node.reservations
+        .iter()
+        .filter(|reservation| !reservation.admits_me && !reservation.is_active())
+        .filter(|reservation| reservation.start_time.is_some())
+        .min_by_key(|reservation| reservation.start_time)
+}
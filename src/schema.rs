@@ -0,0 +1,196 @@
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+
+/// The oldest and newest `data_parser` versions whose output has been checked against.
+const OLDEST_KNOWN: DataParserVersion = DataParserVersion(0, 0, 37);
+const NEWEST_KNOWN: DataParserVersion = DataParserVersion(0, 0, 42);
+
+/// Version of the plugin that printed the JSON, e.g. `v0.0.40` for Slurm 23.11.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DataParserVersion(u32, u32, u32);
+
+impl DataParserVersion {
+    /// Parses the version out of `data_parser/v0.0.40`, `openapi/v0.0.38` or `v0.0.41`.
+    fn parse(s: &str) -> Option<Self> {
+        let version = &s[s.rfind('v')? + 1..];
+        let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+        let version = DataParserVersion(parts.next()??, parts.next()??, parts.next()??);
+        parts.next().is_none().then_some(version)
+    }
+}
+
+impl fmt::Display for DataParserVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+/// Just the `meta` block that every `--json` output starts with.
+#[derive(Deserialize, Debug, Default)]
+struct Envelope {
+    #[serde(default)]
+    meta: Meta,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Meta {
+    #[serde(default)]
+    plugin: Plugin,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Plugin {
+    /// `openapi/v0.0.38` before Slurm 23.02, where there is no `data_parser` entry yet.
+    #[serde(default, rename = "type")]
+    plugin_type: Option<String>,
+    /// `data_parser/v0.0.39` in 23.02, plain `v0.0.40` from 23.11 on.
+    #[serde(default)]
+    data_parser: Option<String>,
+}
+
+/// The `data_parser` version named in the output's `meta.plugin` block, if any.
+pub fn data_parser_version(data: &str) -> Option<DataParserVersion> {
+    let plugin = serde_json::from_str::<Envelope>(data).ok()?.meta.plugin;
+    plugin
+        .data_parser
+        .as_deref()
+        .and_then(DataParserVersion::parse)
+        .or_else(|| plugin.plugin_type.as_deref().and_then(DataParserVersion::parse))
+}
+
+/// Deserializes `--json` output. On failure the error names the detected `data_parser`
+/// version, since a format change between Slurm releases is the usual cause.
+pub fn from_str<T: DeserializeOwned>(data: &str) -> Result<T, Box<dyn Error>> {
+    serde_json::from_str(data).map_err(|error| {
+        let message = match data_parser_version(data) {
+            Some(version) if !(OLDEST_KNOWN..=NEWEST_KNOWN).contains(&version) => format!(
+                "Cannot read JSON from data_parser {} (known versions are {} to {}): {}",
+                version, OLDEST_KNOWN, NEWEST_KNOWN, error
+            ),
+            Some(version) => format!("Cannot read JSON from data_parser {}: {}", version, error),
+            None => format!("Cannot read JSON: {}", error),
+        };
+        message.into()
+    })
+}
+
+/// Slurm's INFINITE and NO_VAL, in their 32 and 64 bit forms, which mark infinite and unset
+/// plain numbers up to v0.0.38.
+const SPECIAL_VALUES: [u64; 4] = [0xffff_ffff, 0xffff_fffe, u64::MAX, u64::MAX - 1];
+
+/// Reads a number that is plain, a numeric string, or wrapped as
+/// `{"set": true, "infinite": false, "number": n}` (data_parser v0.0.39 and later).
+/// Unset and infinite values give `None`.
+pub fn number(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(number) if number.as_u64().is_some_and(|n| SPECIAL_VALUES.contains(&n)) => None,
+        Value::Number(number) => number.as_i64().or_else(|| number.as_f64().map(|n| n as i64)),
+        Value::String(s) => s.trim().parse().ok(),
+        Value::Object(object) => {
+            let flag = |name: &str| object.get(name).and_then(Value::as_bool);
+            if flag("set") == Some(false) || flag("infinite") == Some(true) {
+                return None;
+            }
+            object.get("number").and_then(number)
+        }
+        _ => None,
+    }
+}
+
+/// `deserialize_with` helper for integer fields that newer releases wrap as number objects.
+/// Missing, unset and infinite values read as zero.
+pub fn deserialize_number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i64> + Default,
+{
+    Ok(deserialize_optional_number(deserializer)?.unwrap_or_default())
+}
+
+/// Like `deserialize_number`, but keeps unset and infinite values as `None`.
+pub fn deserialize_optional_number<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i64>,
+{
+    let value = Value::deserialize(deserializer)?;
+    match number(&value) {
+        Some(number) => T::try_from(number)
+            .map(Some)
+            .map_err(|_| de::Error::custom(format!("number out of range: {}", number))),
+        // Unset or infinite, whether as an object or as a plain NO_VAL/INFINITE.
+        None if matches!(value, Value::Null | Value::Number(_) | Value::Object(_)) => Ok(None),
+        None => Err(de::Error::custom(format!("expected a number, found {}", value))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_numbers() {
+        let cases = [
+            (json!(42), Some(42)),
+            (json!(-1), Some(-1)),
+            (json!(2.5), Some(2)),
+            (json!("17"), Some(17)),
+            (json!({"set": true, "infinite": false, "number": 2880}), Some(2880)),
+            (json!({"set": false, "infinite": false, "number": 0}), None),
+            (json!({"set": true, "infinite": true, "number": 0}), None),
+            (json!(0xffff_fffe_u32), None),
+            (json!(0xffff_ffff_u32), None),
+            (json!(u64::MAX - 1), None),
+            (json!({"set": true, "infinite": false, "number": 0xffff_fffe_u32}), None),
+            (json!(null), None),
+            (json!("n/a"), None),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(number(&value), expected, "{}", value);
+        }
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Counts {
+        #[serde(default, deserialize_with = "deserialize_number")]
+        cpus: u32,
+        #[serde(default, deserialize_with = "deserialize_optional_number")]
+        free_memory: Option<u64>,
+    }
+
+    #[test]
+    fn deserializes_numbers() {
+        let cases = [
+            (r#"{"cpus": 64, "free_memory": 300000}"#, Counts { cpus: 64, free_memory: Some(300000) }),
+            (r#"{"cpus": 4294967294, "free_memory": 4294967294}"#, Counts { cpus: 0, free_memory: None }),
+            (r#"{"cpus": 4294967295, "free_memory": 18446744073709551614}"#, Counts { cpus: 0, free_memory: None }),
+            (
+                r#"{"cpus": {"set": true, "infinite": false, "number": 8}, "free_memory": {"set": false, "infinite": false, "number": 0}}"#,
+                Counts { cpus: 8, free_memory: None },
+            ),
+            (r#"{}"#, Counts { cpus: 0, free_memory: None }),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(serde_json::from_str::<Counts>(data).unwrap(), expected, "{}", data);
+        }
+        assert!(serde_json::from_str::<Counts>(r#"{"cpus": "many"}"#).is_err());
+        assert!(serde_json::from_str::<Counts>(r#"{"cpus": 5000000000}"#).is_err());
+    }
+
+    #[test]
+    fn detects_data_parser_versions() {
+        let cases = [
+            (r#"{"meta": {"plugin": {"type": "openapi/v0.0.38"}}}"#, Some(DataParserVersion(0, 0, 38))),
+            (r#"{"meta": {"plugin": {"type": "openapi/v0.0.39", "data_parser": "data_parser/v0.0.39"}}}"#, Some(DataParserVersion(0, 0, 39))),
+            (r#"{"meta": {"plugin": {"type": "", "data_parser": "v0.0.41"}}}"#, Some(DataParserVersion(0, 0, 41))),
+            (r#"{"nodes": []}"#, None),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(data_parser_version(data), expected, "{}", data);
+        }
+    }
+}
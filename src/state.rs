@@ -0,0 +1,73 @@
+use serde::{Deserialize, Deserializer};
+use tui::style::Color;
+
+/// States in which a node cannot start new jobs, whatever its free resources say. Powered down
+/// nodes are left out: Slurm resumes them when a job is scheduled there.
+const UNAVAILABLE_STATES: [&str; 9] = [
+    "DOWN",
+    "DRAIN",
+    "DRAINING",
+    "DRAINED",
+    "FAIL",
+    "FAILING",
+    "MAINT",
+    "NOT_RESPONDING",
+    "FUTURE",
+];
+
+/// Accepts both the old `"state": "idle"` string and the newer `"state": ["IDLE", "DRAIN"]` list.
+pub fn deserialize_state<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StateField {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    let states = match StateField::deserialize(deserializer)? {
+        StateField::One(state) => state.split('+').map(str::to_string).collect(),
+        StateField::Many(states) => states,
+    };
+    Ok(normalize(states))
+}
+
+/// Upper-cases state names, since older Slurm releases report them in lower case.
+pub fn normalize(states: Vec<String>) -> Vec<String> {
+    states
+        .into_iter()
+        .map(|state| state.trim().to_uppercase())
+        .filter(|state| !state.is_empty())
+        .collect()
+}
+
+pub fn is_unavailable(states: &[String]) -> bool {
+    states.iter().any(|state| UNAVAILABLE_STATES.contains(&state.as_str()))
+}
+
+pub fn label(states: &[String]) -> String {
+    if states.is_empty() {
+        "UNKNOWN".to_string()
+    } else {
+        states.join("+")
+    }
+}
+
+pub fn color(states: &[String]) -> Color {
+    let has = |name: &str| states.iter().any(|state| state == name);
+    if has("DOWN") || has("FAIL") || has("NOT_RESPONDING") {
+        Color::Red
+    } else if is_unavailable(states) {
+        Color::LightRed
+    } else if has("RESERVED") {
+        Color::Magenta
+    } else if has("IDLE") {
+        Color::Green
+    } else if has("MIXED") {
+        Color::Yellow
+    } else {
+        Color::Reset
+    }
+}
@@ -0,0 +1,236 @@
+use crate::jobs::Job;
+use crate::partition::Partition;
+use crate::reservation::Reservation;
+use crate::time::{format_duration, format_timestamp, seconds_until};
+use crate::view::ViewOptions;
+use crate::{format_memory, Node};
+use tui::backend::Backend;
+use tui::layout::{Constraint, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::widgets::{Block, Borders, Cell, Row, Table};
+use tui::Frame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Nodes,
+    Jobs,
+    Partitions,
+    Reservations,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 4] = [Tab::Nodes, Tab::Jobs, Tab::Partitions, Tab::Reservations];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Nodes => "Nodes",
+            Tab::Jobs => "Jobs",
+            Tab::Partitions => "Partitions",
+            Tab::Reservations => "Reservations",
+        }
+    }
+
+    pub fn next(self) -> Tab {
+        let index = Tab::ALL.iter().position(|tab| *tab == self).unwrap_or(0);
+        Tab::ALL[(index + 1) % Tab::ALL.len()]
+    }
+}
+
+fn header_row(headers: &[&'static str]) -> Row<'static> {
+    Row::new(
+        headers
+            .iter()
+            .map(|h| Cell::from(*h).style(Style::default().add_modifier(Modifier::BOLD)))
+            .collect::<Vec<_>>(),
+    )
+    .style(Style::default().fg(Color::Yellow))
+}
+
+/// The running jobs, one row each, with a cluster column if `show_cluster` is set.
+pub fn draw_jobs<B: Backend>(f: &mut Frame<B>, area: Rect, jobs: &[&Job], scroll: usize, show_cluster: bool) {
+    let rows: Vec<Row> = jobs
+        .iter()
+        .filter(|job| job.is_active())
+        .skip(scroll)
+        .map(|job| {
+            let time_left = job
+                .end_time()
+                .map(|end_time| format_duration(seconds_until(end_time)))
+                .unwrap_or_else(|| "-".to_string());
+            let cluster = show_cluster
+                .then(|| Cell::from(job.cluster.clone().unwrap_or_default()).style(Style::default().fg(Color::Cyan)));
+            Row::new(cluster.into_iter().chain([
+                Cell::from(job.job_id.to_string()),
+                Cell::from(job.user_name.clone()).style(Style::default().fg(Color::Green)),
+                Cell::from(job.name.clone()),
+                Cell::from(job.partition.clone()).style(Style::default().fg(Color::Blue)),
+                Cell::from(job.job_state.join("+")),
+                Cell::from(job.gpus().to_string()),
+                Cell::from(time_left),
+                Cell::from(job.nodes.clone()),
+            ]).collect::<Vec<_>>())
+        })
+        .collect();
+
+    let cluster = show_cluster.then_some(("Cluster", Constraint::Length(10)));
+    let (headers, widths): (Vec<&'static str>, Vec<Constraint>) = cluster
+        .into_iter()
+        .chain([
+            ("Job ID", Constraint::Length(10)),
+            ("User", Constraint::Length(12)),
+            ("Name", Constraint::Length(24)),
+            ("Partition", Constraint::Length(12)),
+            ("State", Constraint::Length(12)),
+            ("GPUs", Constraint::Length(6)),
+            ("Time Left", Constraint::Length(10)),
+            ("Nodes", Constraint::Min(10)),
+        ])
+        .unzip();
+    let table = Table::new(rows)
+        .header(header_row(&headers))
+        .block(Block::default().borders(Borders::ALL))
+        .widths(&widths)
+        .column_spacing(1);
+    f.render_widget(table, area);
+}
+
+/// Formats a time limit in minutes; `none` stands in for a missing one.
+fn format_limit(minutes: Option<u64>, none: &str) -> String {
+    match minutes {
+        Some(minutes) => format_duration(minutes as i64 * 60),
+        None => none.to_string(),
+    }
+}
+
+/// The partitions with their limits and defaults, and the GPUs of their nodes.
+pub fn draw_partitions<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    partitions: &[&Partition],
+    nodes: &[Node],
+    options: &ViewOptions,
+    scroll: usize,
+    show_cluster: bool,
+) {
+    let rows: Vec<Row> = partitions
+        .iter()
+        .skip(scroll)
+        .map(|partition| {
+            let (free_gpus, total_gpus) = partition.gpus(nodes, options);
+            let is_up = partition.state.iter().all(|state| state == "UP");
+            let cluster = show_cluster
+                .then(|| Cell::from(partition.cluster.clone().unwrap_or_default()).style(Style::default().fg(Color::Cyan)));
+            Row::new(
+                cluster
+                    .into_iter()
+                    .chain([
+                        Cell::from(partition.name.clone()).style(Style::default().fg(Color::Blue)),
+                        Cell::from(partition.state.join("+"))
+                            .style(Style::default().fg(if is_up { Color::Green } else { Color::Red })),
+                        Cell::from(partition.member_nodes(nodes).count().to_string()),
+                        Cell::from(free_gpus.to_string()).style(if free_gpus > 0 {
+                            Style::default().fg(Color::Green)
+                        } else {
+                            Style::default()
+                        }),
+                        Cell::from(total_gpus.to_string()),
+                        Cell::from(format_limit(partition.max_time, "UNLIMITED")),
+                        Cell::from(format_limit(partition.default_time, "-")),
+                        Cell::from(partition.def_cpu_per_gpu().map_or("-".to_string(), |cpus| cpus.to_string())),
+                        Cell::from(partition.def_mem_per_gpu().map_or("-".to_string(), format_memory)),
+                        Cell::from(partition.priority_tier.to_string()),
+                        Cell::from(partition.qos.clone().unwrap_or_else(|| "-".to_string())),
+                    ])
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    let cluster = show_cluster.then_some(("Cluster", Constraint::Length(10)));
+    let (headers, widths): (Vec<&'static str>, Vec<Constraint>) = cluster
+        .into_iter()
+        .chain([
+            ("Partition", Constraint::Length(14)),
+            ("State", Constraint::Length(10)),
+            ("Nodes", Constraint::Length(6)),
+            ("Free GPUs", Constraint::Length(10)),
+            ("Total GPUs", Constraint::Length(10)),
+            ("MaxTime", Constraint::Length(10)),
+            ("DefaultTime", Constraint::Length(11)),
+            ("CPUs/GPU", Constraint::Length(9)),
+            ("Mem/GPU", Constraint::Length(8)),
+            ("Priority", Constraint::Length(8)),
+            ("QoS", Constraint::Min(10)),
+        ])
+        .unzip();
+    let table = Table::new(rows)
+        .header(header_row(&headers))
+        .block(Block::default().borders(Borders::ALL))
+        .widths(&widths)
+        .column_spacing(1);
+    f.render_widget(table, area);
+}
+
+/// The reservations with their times and who may use them.
+pub fn draw_reservations<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    reservations: &[&Reservation],
+    scroll: usize,
+    show_cluster: bool,
+) {
+    let list = |entries: &[String]| if entries.is_empty() { "-".to_string() } else { entries.join(",") };
+    let rows: Vec<Row> = reservations
+        .iter()
+        .skip(scroll)
+        .map(|reservation| {
+            let state = match reservation.start_time {
+                _ if reservation.is_active() => "ACTIVE".to_string(),
+                Some(start_time) => format!("in {}", format_duration(seconds_until(start_time))),
+                None => "-".to_string(),
+            };
+            let cluster = show_cluster
+                .then(|| Cell::from(reservation.cluster.clone().unwrap_or_default()).style(Style::default().fg(Color::Cyan)));
+            Row::new(
+                cluster
+                    .into_iter()
+                    .chain([
+                        Cell::from(reservation.name.clone()).style(Style::default().fg(Color::Blue)),
+                        Cell::from(state),
+                        Cell::from(if reservation.admits_me { "yes" } else { "no" }).style(Style::default().fg(
+                            if reservation.admits_me { Color::Green } else { Color::Red },
+                        )),
+                        Cell::from(reservation.start_time.map_or("-".to_string(), format_timestamp)),
+                        Cell::from(reservation.end_time.map_or("never".to_string(), format_timestamp)),
+                        Cell::from(list(&reservation.users)),
+                        Cell::from(list(&reservation.accounts)),
+                        Cell::from(list(&reservation.flags)),
+                        Cell::from(reservation.node_list.clone()),
+                    ])
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    let cluster = show_cluster.then_some(("Cluster", Constraint::Length(10)));
+    let (headers, widths): (Vec<&'static str>, Vec<Constraint>) = cluster
+        .into_iter()
+        .chain([
+            ("Reservation", Constraint::Length(16)),
+            ("State", Constraint::Length(10)),
+            ("For Me", Constraint::Length(6)),
+            ("Start", Constraint::Length(16)),
+            ("End", Constraint::Length(16)),
+            ("Users", Constraint::Length(16)),
+            ("Accounts", Constraint::Length(16)),
+            ("Flags", Constraint::Length(20)),
+            ("Nodes", Constraint::Min(10)),
+        ])
+        .unzip();
+    let table = Table::new(rows)
+        .header(header_row(&headers))
+        .block(Block::default().borders(Borders::ALL))
+        .widths(&widths)
+        .column_spacing(1);
+    f.render_widget(table, area);
+}
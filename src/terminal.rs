@@ -0,0 +1,69 @@
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use std::io;
+use std::panic;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::thread;
+
+/// Raw mode on the alternate screen, with mouse clicks reported if `mouse` is set, for as long
+/// as the guard lives. While the mouse is captured, the terminal only selects text with Shift held.
+///
+/// The terminal is restored when the guard is dropped, so an early `?` return from the UI
+/// loop leaves a usable shell behind. A panic hook covers panics on the UI thread.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn enter(mouse: bool) -> io::Result<Self> {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // Only the UI thread owns the screen; a panicking worker is reported in the status bar.
+            if thread::current().name() == Some("main") {
+                restore();
+            }
+            default_hook(info);
+        }));
+
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        let result = execute!(stdout, EnterAlternateScreen).and_then(|_| {
+            if mouse {
+                execute!(stdout, EnableMouseCapture)
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(error) = result {
+            restore();
+            return Err(error);
+        }
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Leaves the alternate screen, mouse capture and raw mode. Safe to call more than once.
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), DisableMouseCapture, LeaveAlternateScreen, cursor::Show);
+}
+
+/// A flag that is set when the process receives SIGTERM, SIGHUP or SIGINT, so the UI loop
+/// can exit through the normal path and restore the terminal.
+pub fn shutdown_flag() -> io::Result<Arc<AtomicBool>> {
+    let flag = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGHUP, SIGINT] {
+        signal_hook::flag::register(signal, Arc::clone(&flag))?;
+    }
+    Ok(flag)
+}
@@ -0,0 +1,37 @@
+use chrono::{Local, TimeZone};
+
+/// Formats a Unix timestamp in local time, e.g. `2024-10-14 10:00`. Zero means unset in Slurm.
+pub fn format_timestamp(timestamp: i64) -> String {
+    if timestamp <= 0 {
+        return "-".to_string();
+    }
+    match Local.timestamp_opt(timestamp, 0).single() {
+        Some(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// Seconds from now until `timestamp`, negative once it has passed.
+pub fn seconds_until(timestamp: i64) -> i64 {
+    timestamp - Local::now().timestamp()
+}
+
+/// Formats a duration in seconds compactly, e.g. `2d 3h`, `3h 05m` or `12m`.
+pub fn format_duration(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Parses a local time as Slurm prints it in plain-text output, e.g. `2024-10-14T10:00:00`.
+/// `Unknown`, `N/A` and `None` give `None`.
+pub fn parse_timestamp(s: &str) -> Option<i64> {
+    let time = chrono::NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%dT%H:%M:%S").ok()?;
+    Some(Local.from_local_datetime(&time).earliest()?.timestamp())
+}
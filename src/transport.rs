@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Where the Slurm commands run: here, or on a login node over the system ssh client.
+pub enum Transport {
+    Local,
+    Ssh {
+        /// The ssh command line, `ssh` unless given with `--ssh-command` (e.g. `ssh -J bastion`).
+        command: Vec<String>,
+        host: String,
+        /// Set after the first successful call. The first call may ask for a password on the
+        /// terminal before the TUI starts; later ones reuse the ControlMaster connection and
+        /// run in batch mode, so a lost connection is a refresh error rather than a prompt.
+        connected: AtomicBool,
+    },
+}
+
+/// One master connection per host and user, kept for a while after we exit. Not under
+/// `$TMPDIR`: on macOS that path, the 40 characters of `%C` and the suffix ssh adds while
+/// creating the socket go past the 104 bytes a socket path may have, and ssh then runs
+/// every refresh without the shared connection.
+const CONTROL_PATH: &str = "ControlPath=/tmp/turm_gpu-%C";
+
+impl Transport {
+    pub fn ssh(host: &str, command: &str) -> Self {
+        Transport::Ssh {
+            command: command.split_whitespace().map(str::to_string).collect(),
+            host: host.to_string(),
+            connected: AtomicBool::new(false),
+        }
+    }
+
+    /// Runs `program` and returns its standard output.
+    pub fn run(&self, program: &str, args: &[&str]) -> Result<String, Box<dyn Error>> {
+        match self {
+            Transport::Local => run_command(Command::new(program).args(args), program),
+            Transport::Ssh { command, host, connected } => {
+                let (ssh, ssh_args) = command.split_first().ok_or("Empty --ssh-command")?;
+                let mut ssh_command = Command::new(ssh);
+                ssh_command
+                    .args(ssh_args)
+                    .args(["-o", "ControlMaster=auto", "-o", "ControlPersist=10m", "-o", CONTROL_PATH]);
+                if connected.load(Ordering::Relaxed) {
+                    ssh_command.args(["-o", "BatchMode=yes"]);
+                }
+                // ssh hands the remote command to the login shell, so quote every word.
+                let remote_command: Vec<String> =
+                    std::iter::once(program).chain(args.iter().copied()).map(shell_quote).collect();
+                ssh_command.arg(host).arg("--").arg(remote_command.join(" "));
+
+                let output = run_command(&mut ssh_command, &format!("{} on {}", program, host))?;
+                connected.store(true, Ordering::Relaxed);
+                Ok(output)
+            }
+        }
+    }
+}
+
+fn run_command(command: &mut Command, name: &str) -> Result<String, Box<dyn Error>> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", name, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} failed ({}): {}", name, output.status, stderr.trim()).into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Quotes `word` for a POSIX shell, e.g. `--format=%A|%u` becomes `'--format=%A|%u'`.
+fn shell_quote(word: &str) -> String {
+    if !word.is_empty() && word.chars().all(|c| c.is_ascii_alphanumeric() || "-_=.,/:".contains(c)) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
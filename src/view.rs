@@ -0,0 +1,503 @@
+use crate::hostlist::Pattern;
+use crate::jobs::{self, NodeJob};
+use crate::partition::{self, Partition};
+use crate::reservation;
+use crate::time::{format_duration, seconds_until};
+use crate::{
+    extract_free_memory, extract_free_resources, extract_gpu_info, extract_gpu_types, extract_usable_gpus,
+    format_memory, is_node_fully_allocated, node_states, state, Node,
+};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use tui::style::{Color, Style};
+
+/// The filters and toggles that decide which nodes are shown and how they are counted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewOptions {
+    pub hide_no_free_gpus: bool,
+    pub group_by_partitions: bool,
+    pub group_by_clusters: bool,
+    pub gpu_only_mode: bool,
+    pub gpu_type: Option<String>,
+    /// Only show this cluster of `--clusters`.
+    pub cluster: Option<String>,
+    pub count_unavailable: bool,
+    /// Leave out the partitions the user may not submit to, see `partition::restrict_to_allowed`.
+    pub allowed_only: bool,
+    /// Only show nodes whose name matches.
+    pub node_pattern: Option<Pattern>,
+    /// The column to sort by, within each group; scontrol order when `None`.
+    pub sort: Option<SortKey>,
+    pub sort_descending: bool,
+}
+
+impl ViewOptions {
+    pub fn is_grouped(&self) -> bool {
+        self.group_by_partitions || self.group_by_clusters
+    }
+
+    /// Whether something from `cluster` passes the cluster filter.
+    pub fn includes_cluster(&self, cluster: &Option<String>) -> bool {
+        self.cluster.is_none() || *cluster == self.cluster
+    }
+
+    /// The cluster column is only worth its width with several clusters that are not
+    /// already split into groups.
+    pub fn show_cluster_column(&self, nodes: &[Node]) -> bool {
+        !self.group_by_clusters && self.cluster.is_none() && clusters(nodes).len() > 1
+    }
+}
+
+impl Default for ViewOptions {
+    fn default() -> Self {
+        ViewOptions {
+            hide_no_free_gpus: false,
+            group_by_partitions: false,
+            group_by_clusters: false,
+            gpu_only_mode: true,
+            gpu_type: None,
+            cluster: None,
+            count_unavailable: false,
+            allowed_only: false,
+            node_pattern: None,
+            sort: None,
+            sort_descending: false,
+        }
+    }
+}
+
+pub const HEADERS: [&str; 16] = [
+    "Partitions", "Node", "State", "GPU Type", "Free GPUs", "Next Free", "Max Walltime", "Usable GPUs", "Alloc GPUs",
+    "Total GPUs", "CPU Usage", "Free CPUs", "Mem Usage", "Free Mem", "OS Free", "Reason",
+];
+
+/// A column of the node table to sort by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    Partition,
+    Node,
+    State,
+    GpuType,
+    FreeGpus,
+    NextFree,
+    MaxWalltime,
+    UsableGpus,
+    AllocGpus,
+    TotalGpus,
+    CpuUsage,
+    FreeCpus,
+    MemUsage,
+    FreeMem,
+    OsFree,
+    Reason,
+    Cluster,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 17] = [
+        SortKey::Partition,
+        SortKey::Node,
+        SortKey::State,
+        SortKey::GpuType,
+        SortKey::FreeGpus,
+        SortKey::NextFree,
+        SortKey::MaxWalltime,
+        SortKey::UsableGpus,
+        SortKey::AllocGpus,
+        SortKey::TotalGpus,
+        SortKey::CpuUsage,
+        SortKey::FreeCpus,
+        SortKey::MemUsage,
+        SortKey::FreeMem,
+        SortKey::OsFree,
+        SortKey::Reason,
+        SortKey::Cluster,
+    ];
+
+    /// The header of the column, as in `headers`.
+    pub fn header(self) -> &'static str {
+        match self {
+            SortKey::Partition => "Partitions",
+            SortKey::Node => "Node",
+            SortKey::State => "State",
+            SortKey::GpuType => "GPU Type",
+            SortKey::FreeGpus => "Free GPUs",
+            SortKey::NextFree => "Next Free",
+            SortKey::MaxWalltime => "Max Walltime",
+            SortKey::UsableGpus => "Usable GPUs",
+            SortKey::AllocGpus => "Alloc GPUs",
+            SortKey::TotalGpus => "Total GPUs",
+            SortKey::CpuUsage => "CPU Usage",
+            SortKey::FreeCpus => "Free CPUs",
+            SortKey::MemUsage => "Mem Usage",
+            SortKey::FreeMem => "Free Mem",
+            SortKey::OsFree => "OS Free",
+            SortKey::Reason => "Reason",
+            SortKey::Cluster => "Cluster",
+        }
+    }
+
+    pub fn from_header(header: &str) -> Option<SortKey> {
+        SortKey::ALL.into_iter().find(|key| key.header() == header)
+    }
+
+    /// The next key for the 'o' key: each column in turn, then scontrol order again.
+    pub fn cycle(key: Option<SortKey>) -> Option<SortKey> {
+        match key {
+            Some(key) => SortKey::ALL.get(SortKey::ALL.iter().position(|k| *k == key)? + 1).copied(),
+            None => Some(SortKey::ALL[0]),
+        }
+    }
+}
+
+/// `HEADERS`, with a leading cluster column if `show_cluster` is set.
+pub fn headers(show_cluster: bool) -> Vec<&'static str> {
+    let cluster = show_cluster.then_some("Cluster");
+    cluster.into_iter().chain(HEADERS).collect()
+}
+
+/// Everything shown about one node. The TUI builds these once per refresh or option change.
+#[derive(Debug)]
+pub struct NodeView<'a> {
+    pub node: &'a Node,
+    pub states: Vec<String>,
+    pub gpu_types: Vec<String>,
+    pub free_gpus: u32,
+    pub usable_gpus: u32,
+    pub alloc_gpus: u32,
+    pub total_gpus: u32,
+    pub free_cpus: u32,
+    pub free_memory: u64,
+    /// When the next GPU is expected to free up, for nodes that have none free now.
+    pub next_free_at: Option<i64>,
+    /// How long a job started now may run here, in seconds; `None` when unlimited.
+    pub max_walltime: Option<i64>,
+    pub is_unavailable: bool,
+    pub is_fully_allocated: bool,
+}
+
+impl<'a> NodeView<'a> {
+    pub fn new(node: &'a Node, partitions: &[Partition], node_jobs: &[NodeJob], options: &ViewOptions) -> Self {
+        let gpu_type = options.gpu_type.as_deref();
+        let (alloc_gpus, total_gpus) = extract_gpu_info(node, gpu_type);
+        let (free_gpus, free_cpus) = extract_free_resources(node, gpu_type, options.count_unavailable);
+        let states = node_states(node);
+        let is_unavailable = state::is_unavailable(&states);
+        let next_free_at = if total_gpus == 0 {
+            None
+        } else if let Some(reservation) = reservation::blocking(node) {
+            reservation.end_time
+        } else if free_gpus == 0 && (!is_unavailable || options.count_unavailable) {
+            jobs::next_gpu_release(node_jobs, gpu_type)
+        } else {
+            None
+        };
+        NodeView {
+            node,
+            next_free_at,
+            max_walltime: max_walltime(node, partitions),
+            is_unavailable,
+            states,
+            gpu_types: extract_gpu_types(node),
+            free_gpus,
+            usable_gpus: extract_usable_gpus(node, partitions, gpu_type, options.count_unavailable),
+            alloc_gpus,
+            total_gpus,
+            free_cpus,
+            free_memory: extract_free_memory(node, options.count_unavailable),
+            is_fully_allocated: is_node_fully_allocated(
+                node,
+                partitions,
+                options.gpu_only_mode,
+                gpu_type,
+                options.count_unavailable,
+            ),
+        }
+    }
+
+    /// The reason text for nodes that cannot take jobs, or the reservation that holds or
+    /// will hold the node for others.
+    pub fn reason(&self) -> String {
+        if self.is_unavailable {
+            return self.node.reason.clone().unwrap_or_default();
+        }
+        if let Some(reservation) = reservation::blocking(self.node) {
+            return format!("reserved: {}", reservation.name);
+        }
+        match reservation::upcoming(self.node) {
+            Some(reservation) => format!(
+                "reserved: {} in {}",
+                reservation.name,
+                format_duration(reservation.start_time.map_or(0, seconds_until))
+            ),
+            None => String::new(),
+        }
+    }
+
+    /// The longest job that could start here now, `-` on nodes that cannot take jobs.
+    pub fn max_walltime(&self) -> String {
+        if self.is_unavailable {
+            return "-".to_string();
+        }
+        match self.max_walltime {
+            Some(seconds) => format_duration(seconds),
+            None => "UNLIMITED".to_string(),
+        }
+    }
+
+    /// `now` when a GPU is free, otherwise the time until the next one frees up.
+    pub fn next_free(&self) -> String {
+        if self.free_gpus > 0 {
+            "now".to_string()
+        } else {
+            match self.next_free_at {
+                Some(time) => format!("in {}", format_duration(seconds_until(time))),
+                None => "-".to_string(),
+            }
+        }
+    }
+
+    /// The text and style of each column in `headers(show_cluster)`. The partition column is
+    /// left empty when `show_partitions` is off, as under a partition group header.
+    pub fn columns(&self, show_partitions: bool, show_cluster: bool) -> Vec<(String, Style)> {
+        let node = self.node;
+        let green_if = |condition: bool| {
+            if condition {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default()
+            }
+        };
+
+        let partitions = if show_partitions { node.partitions.join(", ") } else { String::new() };
+        let name_style = if self.is_fully_allocated {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default().fg(Color::Green)
+        };
+        let usable_gpu_style = if self.usable_gpus == 0 && self.free_gpus > 0 {
+            Style::default().fg(Color::Red)
+        } else {
+            green_if(self.usable_gpus > 0)
+        };
+
+        let cluster = show_cluster.then(|| (node.cluster.clone().unwrap_or_default(), Style::default().fg(Color::Cyan)));
+        cluster.into_iter().chain([
+            (partitions, Style::default().fg(Color::Blue)),
+            (node.name.clone(), name_style),
+            (state::label(&self.states), Style::default().fg(state::color(&self.states))),
+            (self.gpu_types.join(", "), Style::default()),
+            (self.free_gpus.to_string(), green_if(self.free_gpus > 0)),
+            (self.next_free(), green_if(self.free_gpus > 0)),
+            (self.max_walltime(), Style::default()),
+            (self.usable_gpus.to_string(), usable_gpu_style),
+            (self.alloc_gpus.to_string(), Style::default()),
+            (self.total_gpus.to_string(), Style::default()),
+            (format!("{}/{}", node.alloc_cpus, node.cpus), Style::default()),
+            (self.free_cpus.to_string(), green_if(self.free_cpus > 0)),
+            (format!("{}/{}", format_memory(node.alloc_memory), format_memory(node.real_memory)), Style::default()),
+            (format_memory(self.free_memory), green_if(self.free_memory > 0)),
+            (node.free_mem.map(format_memory).unwrap_or_default(), Style::default()),
+            (self.reason(), Style::default().fg(Color::LightRed)),
+        ])
+        .collect()
+    }
+}
+
+/// How long a job started now may run on `node`, in seconds: the longest MaxTime of its
+/// partitions, cut short by the next reservation on the node. A job outside a reservation
+/// has to end before it starts, even one the user is part of. `None` when unlimited.
+pub fn max_walltime(node: &Node, partitions: &[Partition]) -> Option<i64> {
+    if reservation::blocking(node).is_some() {
+        return Some(0);
+    }
+    // Partitions missing from the partition list are taken to be unlimited.
+    let max_time = node
+        .partitions
+        .iter()
+        .map(|name| partition::find(partitions, node, name).and_then(|partition| partition.max_time))
+        .collect::<Option<Vec<u64>>>()
+        .and_then(|limits| limits.into_iter().max())
+        .map(|minutes| minutes as i64 * 60);
+    let until_reservation = reservation::next_start(node).map(seconds_until);
+    match (max_time, until_reservation) {
+        (Some(max_time), Some(until)) => Some(max_time.min(until)),
+        (max_time, until) => max_time.or(until),
+    }
+}
+
+/// Applies the cluster, node name, GPU type and free node filters, and sorts the result as `options.sort` asks.
+/// `jobs_by_node` is the index from `jobs::jobs_by_node`.
+pub fn filter_nodes<'a>(
+    nodes: &'a [Node],
+    partitions: &[Partition],
+    jobs_by_node: &HashMap<String, Vec<NodeJob>>,
+    options: &ViewOptions,
+) -> Vec<NodeView<'a>> {
+    let mut views: Vec<NodeView> = filter_unsorted(nodes, partitions, options)
+        .into_iter()
+        .map(|node| NodeView::new(node, partitions, jobs::jobs_on(jobs_by_node, node), options))
+        .collect();
+    if let Some(key) = options.sort {
+        sort_nodes(&mut views, options, key);
+    }
+    views
+}
+
+fn filter_unsorted<'a>(nodes: &'a [Node], partitions: &[Partition], options: &ViewOptions) -> Vec<&'a Node> {
+    let gpu_type = options.gpu_type.as_deref();
+    let typed_nodes = nodes
+        .iter()
+        .filter(|node| options.includes_cluster(&node.cluster))
+        .filter(|node| options.node_pattern.as_ref().is_none_or(|pattern| pattern.matches(&node.name)))
+        .filter(|node| gpu_type.is_none_or(|gpu_type| extract_gpu_types(node).iter().any(|t| t == gpu_type)));
+
+    if options.hide_no_free_gpus {
+        typed_nodes
+            .filter(|node| {
+                let (_, total_gpus) = extract_gpu_info(node, gpu_type);
+                let (_, free_cpus) = extract_free_resources(node, gpu_type, options.count_unavailable);
+                let free_gpus = extract_usable_gpus(node, partitions, gpu_type, options.count_unavailable);
+                if options.gpu_only_mode {
+                    // GPU-only 모드일 때는 GPU 상태만 체크
+                    if total_gpus > 0 {
+                        free_gpus > 0
+                    } else {
+                        free_cpus > 0
+                    }
+                } else {
+                    // 기존 모드에서는 GPU와 CPU 모두 체크
+                    free_gpus > 0 || free_cpus > 0
+                }
+            })
+            .collect()
+    } else {
+        typed_nodes.collect()
+    }
+}
+
+/// What a node is sorted by. Every key yields the same variant.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortValue {
+    Number(i64),
+    Text(Vec<NamePart>),
+}
+
+/// A run of digits or of other characters in a name, so that `gpu2` sorts before `gpu10`.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum NamePart {
+    /// Digits without leading zeros, compared by length first.
+    Number(usize, String),
+    Text(String),
+}
+
+fn natural_key(name: &str) -> Vec<NamePart> {
+    let mut parts = Vec::new();
+    let mut rest = name;
+    while let Some(first) = rest.chars().next() {
+        let is_digit = first.is_ascii_digit();
+        let end = rest.find(|c: char| c.is_ascii_digit() != is_digit).unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        parts.push(if is_digit {
+            let digits = run.trim_start_matches('0');
+            NamePart::Number(digits.len(), digits.to_string())
+        } else {
+            NamePart::Text(run.to_string())
+        });
+        rest = tail;
+    }
+    parts
+}
+
+/// Sorts `views` by `key`, keeping scontrol order between equal nodes.
+fn sort_nodes(views: &mut Vec<NodeView>, options: &ViewOptions, key: SortKey) {
+    let mut keyed: Vec<(SortValue, NodeView)> = views
+        .drain(..)
+        .map(|view| {
+            let node = view.node;
+            let value = match key {
+                SortKey::Partition => SortValue::Text(natural_key(&node.partitions.join(","))),
+                SortKey::Node => SortValue::Text(natural_key(&node.name)),
+                SortKey::State => SortValue::Text(natural_key(&state::label(&view.states))),
+                SortKey::GpuType => SortValue::Text(natural_key(&view.gpu_types.join(","))),
+                SortKey::FreeGpus => SortValue::Number(view.free_gpus.into()),
+                // Free now first, then by when a GPU frees up; never last.
+                SortKey::NextFree if view.free_gpus > 0 => SortValue::Number(0),
+                SortKey::NextFree => SortValue::Number(view.next_free_at.unwrap_or(i64::MAX)),
+                SortKey::MaxWalltime => SortValue::Number(view.max_walltime.unwrap_or(i64::MAX)),
+                SortKey::UsableGpus => SortValue::Number(view.usable_gpus.into()),
+                SortKey::AllocGpus => SortValue::Number(view.alloc_gpus.into()),
+                SortKey::TotalGpus => SortValue::Number(view.total_gpus.into()),
+                SortKey::CpuUsage => SortValue::Number(per_mille(node.alloc_cpus.into(), node.cpus.into())),
+                SortKey::FreeCpus => SortValue::Number(view.free_cpus.into()),
+                SortKey::MemUsage => SortValue::Number(per_mille(node.alloc_memory, node.real_memory)),
+                SortKey::FreeMem => SortValue::Number(view.free_memory as i64),
+                SortKey::OsFree => SortValue::Number(node.free_mem.map_or(-1, |free_mem| free_mem as i64)),
+                SortKey::Reason => SortValue::Text(natural_key(&view.reason())),
+                SortKey::Cluster => SortValue::Text(natural_key(node.cluster.as_deref().unwrap_or(""))),
+            };
+            (value, view)
+        })
+        .collect();
+    keyed.sort_by(|a, b| {
+        let order = a.0.cmp(&b.0);
+        if options.sort_descending {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+    *views = keyed.into_iter().map(|(_, view)| view).collect();
+}
+
+/// How much of `total` is `used`, in thousandths.
+fn per_mille(used: u64, total: u64) -> i64 {
+    (used * 1000 / total.max(1)) as i64
+}
+
+/// Groups nodes by partition, cluster or both (`cluster / partition`), sorted by name.
+/// Nodes in several partitions appear in each.
+pub fn group_nodes<'v, 'a>(views: &'v [NodeView<'a>], options: &ViewOptions) -> Vec<(String, Vec<&'v NodeView<'a>>)> {
+    let mut partition_map: HashMap<String, Vec<&NodeView>> = HashMap::new();
+    for view in views {
+        let node = view.node;
+        let cluster = node.cluster.as_deref().unwrap_or("local");
+        let groups: Vec<String> = match (options.group_by_clusters, options.group_by_partitions) {
+            (true, true) => node.partitions.iter().map(|partition| format!("{} / {}", cluster, partition)).collect(),
+            (true, false) => vec![cluster.to_string()],
+            _ => node.partitions.clone(),
+        };
+        for group in groups {
+            partition_map
+                .entry(group)
+                .or_default()
+                .push(view);
+        }
+    }
+    let mut partition_list: Vec<(String, Vec<&NodeView>)> = partition_map.into_iter().collect();
+    partition_list.sort_by(|a, b| a.0.cmp(&b.0));
+    partition_list
+}
+
+/// The distinct clusters of `nodes`, sorted. Empty unless loaded with `--clusters`.
+pub fn clusters(nodes: &[Node]) -> Vec<String> {
+    let mut clusters: Vec<String> = nodes.iter().filter_map(|node| node.cluster.clone()).collect();
+    clusters.sort();
+    clusters.dedup();
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_names_naturally() {
+        let mut names = ["gpu10", "gpu2", "gpu010", "cpu1", "gpu01", "gpu", "gpu1a", "gpu0"];
+        names.sort_by_key(|name| natural_key(name));
+        assert_eq!(names, ["cpu1", "gpu", "gpu0", "gpu01", "gpu1a", "gpu2", "gpu10", "gpu010"]);
+        assert!(natural_key("gpu2") < natural_key("gpu10"));
+        assert!(natural_key("gpu02") == natural_key("gpu2"));
+        assert!(natural_key("rack2-gpu10") < natural_key("rack10-gpu2"));
+    }
+}
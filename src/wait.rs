@@ -0,0 +1,97 @@
+use crate::cli::WaitArgs;
+use crate::fit::{self, JobRequest};
+use crate::partition::{self, Partition};
+use crate::{reservation, Node};
+use crate::source::{self, NodeSource};
+use crate::REFRESH_INTERVAL;
+use std::error::Error;
+use std::io::Write;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Exit status when `--timeout` runs out, as `timeout(1)` uses.
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// Polls `source` every refresh until the requested job fits somewhere, then rings the bell,
+/// prints the matching nodes and runs the user's command. Returns the process exit status.
+/// Polls that fail are reported on stderr and retried until `--timeout`.
+/// With `allowed_only`, only partitions the user may submit to are considered.
+pub fn run(source: &mut dyn NodeSource, args: &WaitArgs, allowed_only: bool) -> Result<i32, Box<dyn Error>> {
+    let request = JobRequest::from_args(&args.request)?;
+    let timeout = args
+        .timeout
+        .as_deref()
+        .map(|timeout| parse_duration(timeout).ok_or_else(|| format!("Invalid --timeout={}", timeout)))
+        .transpose()?;
+    let started = Instant::now();
+
+    loop {
+        match load(source, allowed_only) {
+            Ok((nodes, partitions)) => {
+                let fits = fit::find_fits(&nodes, &partitions, &request);
+                if !fits.is_empty() {
+                    // The bell goes to stderr so that stdout stays clean for scripts.
+                    eprint!("\x07");
+                    fit::print_fits(&fits);
+                    std::io::stdout().flush()?;
+                    return run_command(&args.command);
+                }
+            }
+            // A failed poll is retried on the next one rather than ending the wait.
+            Err(error) => eprintln!("Failed to load nodes: {}", error),
+        }
+
+        if timeout.is_some_and(|timeout| started.elapsed() + REFRESH_INTERVAL > timeout) {
+            eprintln!("Timed out waiting for resources.");
+            return Ok(TIMEOUT_EXIT_CODE);
+        }
+        thread::sleep(REFRESH_INTERVAL);
+    }
+}
+
+/// The nodes and partitions to fit the job into, as of now.
+fn load(source: &mut dyn NodeSource, allowed_only: bool) -> Result<(Vec<Node>, Vec<Partition>), Box<dyn Error>> {
+    let mut nodes = source.load_nodes()?;
+    let partitions = source.load_partitions()?;
+    let mut warnings = Vec::new();
+    let reservations = source::or_warn(source.load_reservations(), "reservations", &mut warnings);
+    reservation::mark_nodes(&mut nodes, &reservations);
+    warnings.extend(source.take_warnings());
+    for warning in warnings {
+        eprintln!("{}", warning);
+    }
+    if allowed_only {
+        return Ok(partition::restrict_to_allowed(&nodes, &partitions));
+    }
+    Ok((nodes, partitions))
+}
+
+fn run_command(command: &[String]) -> Result<i32, Box<dyn Error>> {
+    let Some((program, args)) = command.split_first() else {
+        return Ok(0);
+    };
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Parses `90s`, `30m`, `2h` or `1d`. Plain numbers are seconds.
+fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => (&s[..i], &s[i..]),
+        None => (s, ""),
+    };
+    let value: u64 = digits.parse().ok()?;
+    let seconds = match unit {
+        "" | "s" => value,
+        "m" => value.checked_mul(60)?,
+        "h" => value.checked_mul(60 * 60)?,
+        "d" => value.checked_mul(24 * 60 * 60)?,
+        _ => return None,
+    };
+    Some(Duration::from_secs(seconds))
+}